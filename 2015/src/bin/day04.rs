@@ -1,6 +1,6 @@
 #![feature(test)]
 extern crate test;
-use aoc_2015::*;
+use aoc_2015::{hash_search::PrefixHasher, *};
 const DAY: u8 = 4;
 
 fn main() {
@@ -14,17 +14,9 @@ fn main() {
 }
 
 fn start_with_zeroes(input: &str, n: usize) -> usize {
-    (0..)
-        .find_map(|i| {
-            let input = format!("{}{}", input.trim(), i);
-            let hash = format!("{:x}", md5::compute(input));
-            if hash.chars().take(n).all(|c| c == '0') {
-                Some(i)
-            } else {
-                None
-            }
-        })
-        .unwrap()
+    PrefixHasher::new(input.trim())
+        .find(n, 0)
+        .expect("Some number should give enough zeroes")
 }
 
 pub mod p1 {
@@ -42,4 +34,26 @@ pub mod p2 {
 }
 
 #[cfg(test)]
-mod day04_tests {}
+mod day04_tests {
+    use super::*;
+
+    const SAMPLE: &str = include_str!("../../inputs/day04/sample.txt");
+
+    #[test]
+    fn p1_sample() {
+        assert_eq!(p1::solve(SAMPLE), 609043);
+        assert_eq!(p1::solve("pqrstuv"), 1048970);
+    }
+
+    #[test]
+    fn p1_input() {
+        let input = &read_input(DAY);
+        assert_eq!(p1::solve(input), 254575)
+    }
+
+    #[test]
+    fn p2_input() {
+        let input = &read_input(DAY);
+        assert_eq!(p2::solve(input), 1038736)
+    }
+}
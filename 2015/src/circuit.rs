@@ -0,0 +1,490 @@
+//! Bitwise logic circuits made of named wires, like the ones Bobby Tables builds in day 7.
+//!
+//! Wires are evaluated on demand with an explicit stack rather than recursion, so deep circuits
+//! can't blow the call stack, and evaluated values are cached until something upstream changes.
+use crate::parse_error::ParseError;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+#[derive(Hash, Eq, PartialEq, Debug, Clone)]
+pub enum Signal {
+    Value(u64),
+    Variable(String),
+}
+
+// What was expected in place of a signal or gate that didn't parse
+const SIGNAL: &str = "a signal";
+const GATE: &str = "a gate";
+const GATE_NAME: &str = "AND, OR, LSHIFT or RSHIFT";
+
+impl FromStr for Signal {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(n) = s.parse::<u64>() {
+            Ok(Signal::Value(n))
+        } else if !s.starts_with(|c: char| c.is_ascii_lowercase())
+            || !s
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        {
+            Err(SIGNAL)
+        } else {
+            Ok(Signal::Variable(s.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for Signal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Signal::Value(n) => write!(f, "{n}"),
+            Signal::Variable(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Operation {
+    Set(Signal),
+    And(Signal, Signal),
+    Or(Signal, Signal),
+    LShift(Signal, Signal),
+    RShift(Signal, Signal),
+    Not(Signal),
+}
+
+impl Operation {
+    /// The signals feeding this gate
+    pub fn inputs(&self) -> Vec<&Signal> {
+        match self {
+            Operation::Set(a) | Operation::Not(a) => vec![a],
+            Operation::And(a, b)
+            | Operation::Or(a, b)
+            | Operation::LShift(a, b)
+            | Operation::RShift(a, b) => vec![a, b],
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Operation::Set(_) => "SET",
+            Operation::And(..) => "AND",
+            Operation::Or(..) => "OR",
+            Operation::LShift(..) => "LSHIFT",
+            Operation::RShift(..) => "RSHIFT",
+            Operation::Not(_) => "NOT",
+        }
+    }
+}
+
+impl FromStr for Operation {
+    type Err = &'static str;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s.split_whitespace().collect::<Vec<&str>>();
+        match parts[..] {
+            [a] => Ok(Operation::Set(a.parse()?)),
+            ["NOT", a] => Ok(Operation::Not(a.parse()?)),
+            [a, op, b] => {
+                let (a, b) = (a.parse()?, b.parse()?);
+                match op {
+                    "AND" => Ok(Operation::And(a, b)),
+                    "OR" => Ok(Operation::Or(a, b)),
+                    "LSHIFT" => Ok(Operation::LShift(a, b)),
+                    "RSHIFT" => Ok(Operation::RShift(a, b)),
+                    _ => Err(GATE_NAME),
+                }
+            }
+            _ => Err(GATE),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CircuitError {
+    /// A line of the instructions couldn't be read
+    Parse(ParseError),
+    /// A wire is read but nothing drives it
+    Undriven(String),
+    /// The wires form a loop, listed in the order they feed each other
+    Cycle(Vec<String>),
+}
+
+impl fmt::Display for CircuitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitError::Parse(e) => write!(f, "{e}"),
+            CircuitError::Undriven(wire) => write!(f, "wire `{wire}` has no input"),
+            CircuitError::Cycle(wires) => write!(f, "wires form a cycle: {}", wires.join(" -> ")),
+        }
+    }
+}
+
+impl std::error::Error for CircuitError {}
+
+#[derive(Debug, Clone)]
+pub struct Circuit {
+    width: u32,
+    gates: HashMap<String, Operation>,
+    /// Wires whose gate reads the key wire
+    dependents: HashMap<String, HashSet<String>>,
+    overrides: HashMap<String, u64>,
+    values: HashMap<String, u64>,
+}
+
+impl Default for Circuit {
+    fn default() -> Self {
+        Self::new(16)
+    }
+}
+
+impl Circuit {
+    /// An empty circuit where every wire carries `width` bits
+    pub fn new(width: u32) -> Self {
+        assert!((1..=64).contains(&width), "width must be 1 to 64 bits");
+        Self {
+            width,
+            gates: Default::default(),
+            dependents: Default::default(),
+            overrides: Default::default(),
+            values: Default::default(),
+        }
+    }
+
+    /// Read one `<gate> -> <wire>` per line
+    pub fn parse(input: &str, width: u32) -> Result<Self, CircuitError> {
+        let error =
+            |part, expected| CircuitError::Parse(ParseError::at_slice(input, part, expected));
+        let mut circuit = Self::new(width);
+        for line in input.lines() {
+            let (gate, output) = line
+                .split_once(" -> ")
+                .ok_or_else(|| error(line, "`<gate> -> <wire>`"))?;
+            let output = output.trim();
+            let Signal::Variable(output) = output.parse().map_err(|e| error(output, e))? else {
+                return Err(error(output, "a wire, not a constant"));
+            };
+            let gate = gate.parse().map_err(|e| error(Self::blame(gate, e), e))?;
+            circuit.insert(&output, gate);
+        }
+        Ok(circuit)
+    }
+
+    /// The part of a gate that made it fail to parse with `expected`
+    fn blame<'a>(gate: &'a str, expected: &str) -> &'a str {
+        let mut tokens = gate.split_whitespace();
+        let bad = match expected {
+            SIGNAL => tokens.find(|t| {
+                !["NOT", "AND", "OR", "LSHIFT", "RSHIFT"].contains(t)
+                    && t.parse::<Signal>().is_err()
+            }),
+            GATE_NAME => tokens.nth(1),
+            _ => None,
+        };
+        bad.unwrap_or(gate)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn mask(&self) -> u64 {
+        u64::MAX >> (64 - self.width)
+    }
+
+    pub fn gates(&self) -> &HashMap<String, Operation> {
+        &self.gates
+    }
+
+    /// Drive `wire` with `gate`, replacing whatever drove it before
+    pub fn insert(&mut self, wire: &str, gate: Operation) {
+        if let Some(old) = self.gates.get(wire) {
+            for input in old.inputs() {
+                if let Signal::Variable(name) = input {
+                    if let Some(d) = self.dependents.get_mut(name) {
+                        d.remove(wire);
+                    }
+                }
+            }
+        }
+        for input in gate.inputs() {
+            if let Signal::Variable(name) = input {
+                self.dependents
+                    .entry(name.clone())
+                    .or_default()
+                    .insert(wire.to_string());
+            }
+        }
+        self.gates.insert(wire.to_string(), gate);
+        self.invalidate(wire);
+    }
+
+    /// Force `wire` to `value` regardless of its gate, like cutting it and wiring in a constant
+    pub fn set_override(&mut self, wire: &str, value: u64) {
+        self.overrides.insert(wire.to_string(), value & self.mask());
+        self.invalidate(wire);
+    }
+
+    /// Hand `wire` back to its gate
+    pub fn clear_override(&mut self, wire: &str) {
+        if self.overrides.remove(wire).is_some() {
+            self.invalidate(wire);
+        }
+    }
+
+    /// Forget the cached value of `wire` and everything downstream of it
+    fn invalidate(&mut self, wire: &str) {
+        self.values.remove(wire);
+        let mut stack = vec![wire.to_string()];
+        while let Some(w) = stack.pop() {
+            for d in self.dependents.get(&w).into_iter().flatten() {
+                // a wire that isn't cached can't have anything cached downstream of it
+                if self.values.remove(d).is_some() {
+                    stack.push(d.clone());
+                }
+            }
+        }
+    }
+
+    /// Number of wires with an up to date value
+    pub fn cached(&self) -> usize {
+        self.values.len()
+    }
+
+    /// The signal on `wire`, evaluating whatever it depends on first
+    pub fn get(&mut self, wire: &str) -> Result<u64, CircuitError> {
+        // (wire, inputs already pushed)
+        let mut stack = vec![(wire.to_string(), false)];
+        let mut path: Vec<String> = vec![];
+        let mut on_path = HashSet::new();
+
+        while let Some((w, expanded)) = stack.pop() {
+            if expanded {
+                let v = self.compute(&w);
+                on_path.remove(&w);
+                self.values.insert(w, v);
+                path.pop();
+                continue;
+            }
+            if self.values.contains_key(&w) {
+                continue;
+            }
+            if let Some(&v) = self.overrides.get(&w) {
+                self.values.insert(w, v);
+                continue;
+            }
+            if on_path.contains(&w) {
+                let start = path.iter().position(|p| *p == w).unwrap_or_default();
+                let mut cycle = path[start..].to_vec();
+                cycle.push(w);
+                return Err(CircuitError::Cycle(cycle));
+            }
+            let gate = self
+                .gates
+                .get(&w)
+                .ok_or_else(|| CircuitError::Undriven(w.clone()))?;
+
+            stack.push((w.clone(), true));
+            for input in gate.inputs() {
+                if let Signal::Variable(name) = input {
+                    if !self.values.contains_key(name) {
+                        stack.push((name.clone(), false));
+                    }
+                }
+            }
+            on_path.insert(w.clone());
+            path.push(w);
+        }
+
+        Ok(self.values[wire])
+    }
+
+    /// Apply the gate for `wire`, all of its inputs must already be evaluated
+    fn compute(&self, wire: &str) -> u64 {
+        let read = |s: &Signal| match s {
+            Signal::Value(n) => *n & self.mask(),
+            Signal::Variable(name) => self.values[name],
+        };
+        let shift = |a: u64, b: u64, f: fn(u64, u32) -> u64| {
+            if b >= self.width as u64 {
+                0
+            } else {
+                f(a, b as u32)
+            }
+        };
+        let v = match &self.gates[wire] {
+            Operation::Set(a) => read(a),
+            Operation::And(a, b) => read(a) & read(b),
+            Operation::Or(a, b) => read(a) | read(b),
+            Operation::LShift(a, b) => shift(read(a), read(b), |a, b| a << b),
+            Operation::RShift(a, b) => shift(read(a), read(b), |a, b| a >> b),
+            Operation::Not(a) => !read(a),
+        };
+        v & self.mask()
+    }
+
+    /// Render the circuit as a Graphviz DOT digraph, one box per gate feeding its output wire
+    pub fn to_dot(&self) -> String {
+        let mut wires = self.gates.keys().collect::<Vec<_>>();
+        wires.sort();
+
+        let mut dot = String::from("digraph circuit {\n    rankdir=LR;\n");
+        for wire in wires {
+            let gate = &self.gates[wire];
+            let node = match gate {
+                Operation::Set(_) => wire.clone(),
+                _ => {
+                    let node = format!("{wire}_gate");
+                    writeln!(
+                        dot,
+                        "    \"{node}\" [label=\"{}\", shape=box];",
+                        gate.name()
+                    )
+                    .unwrap();
+                    writeln!(dot, "    \"{node}\" -> \"{wire}\";").unwrap();
+                    node
+                }
+            };
+            for (i, input) in gate.inputs().into_iter().enumerate() {
+                let from = match input {
+                    Signal::Variable(name) => name.clone(),
+                    Signal::Value(n) => {
+                        let constant = format!("{wire}_const{i}");
+                        writeln!(dot, "    \"{constant}\" [label=\"{n}\", shape=plaintext];")
+                            .unwrap();
+                        constant
+                    }
+                };
+                writeln!(dot, "    \"{from}\" -> \"{node}\";").unwrap();
+            }
+            if let Some(v) = self.overrides.get(wire) {
+                writeln!(dot, "    \"{wire}\" [xlabel=\"= {v}\"];").unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl FromStr for Circuit {
+    type Err = CircuitError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, 16)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "123 -> x
+456 -> y
+x AND y -> d
+x OR y -> e
+x LSHIFT 2 -> f
+y RSHIFT 2 -> g
+NOT x -> h
+NOT y -> i";
+
+    #[test]
+    fn evaluates_sample() {
+        let mut c: Circuit = SAMPLE.parse().unwrap();
+        assert_eq!(c.get("h"), Ok(65412));
+        assert_eq!(c.get("f"), Ok(492));
+        assert_eq!(c.get("g"), Ok(114));
+    }
+
+    #[test]
+    fn width_masks_values() {
+        let mut c = Circuit::parse(SAMPLE, 8).unwrap();
+        assert_eq!(c.get("x"), Ok(123));
+        assert_eq!(c.get("h"), Ok(!123u8 as u64));
+        assert_eq!(c.get("y"), Ok(456 & 0xff));
+        assert_eq!(c.get("f"), Ok((123u8 << 2) as u64));
+    }
+
+    #[test]
+    fn deep_chains_dont_recurse() {
+        let mut input = String::from("1 -> w0\n");
+        for i in 1..100_000 {
+            writeln!(input, "w{} -> w{i}", i - 1).unwrap();
+        }
+        let mut c = Circuit::parse(&input, 16).unwrap();
+        assert_eq!(c.get("w99999"), Ok(1));
+    }
+
+    #[test]
+    fn cycles_are_reported() {
+        let mut c = Circuit::parse("a AND b -> c\nc -> d\nd OR 1 -> a\n2 -> b", 16).unwrap();
+        assert_eq!(
+            c.get("d"),
+            Err(CircuitError::Cycle(vec![
+                "d".into(),
+                "c".into(),
+                "a".into(),
+                "d".into()
+            ]))
+        );
+    }
+
+    #[test]
+    fn parse_errors_point_at_the_input() {
+        let located = |input| match Circuit::parse(input, 16).unwrap_err() {
+            CircuitError::Parse(e) => (e.line, e.column, e.expected),
+            e => panic!("{e} isn't a parse error"),
+        };
+        assert_eq!(located("123 -> x\nx AND -> y"), (2, 1, GATE.into()));
+        assert_eq!(located("123 -> x\nx AND Y -> y"), (2, 7, SIGNAL.into()));
+        assert_eq!(located("x XOR y -> z"), (1, 3, GATE_NAME.into()));
+        assert_eq!(
+            located("1 -> x\n\n2 -> 3"),
+            (2, 1, "`<gate> -> <wire>`".into())
+        );
+        assert_eq!(
+            Circuit::parse("123 -> x\nx AND Y -> y", 16)
+                .unwrap_err()
+                .to_string(),
+            "expected a signal at line 2, column 7\n  |\n2 | x AND Y -> y\n  |       ^"
+        );
+    }
+
+    #[test]
+    fn undriven_wire() {
+        let mut c = Circuit::parse("x -> y", 16).unwrap();
+        assert_eq!(c.get("y"), Err(CircuitError::Undriven("x".into())));
+    }
+
+    #[test]
+    fn overrides_only_reevaluate_downstream() {
+        let mut c = Circuit::parse(&format!("{SAMPLE}\n1 -> z\nz LSHIFT 1 -> q"), 16).unwrap();
+        c.get("e").unwrap();
+        c.get("q").unwrap();
+        assert_eq!(c.cached(), 5);
+
+        c.set_override("y", 0);
+        // x, z and q are untouched by y
+        assert_eq!(c.cached(), 3);
+        assert_eq!(c.get("e"), Ok(123));
+
+        c.clear_override("y");
+        assert_eq!(c.get("e"), Ok(507));
+    }
+
+    #[test]
+    fn dot_export() {
+        let c = Circuit::parse("123 -> x\nx AND y -> d", 16).unwrap();
+        assert_eq!(
+            c.to_dot(),
+            r#"digraph circuit {
+    rankdir=LR;
+    "d_gate" [label="AND", shape=box];
+    "d_gate" -> "d";
+    "x" -> "d_gate";
+    "y" -> "d_gate";
+    "x_const0" [label="123", shape=plaintext];
+    "x_const0" -> "x";
+}
+"#
+        );
+    }
+}
@@ -0,0 +1,160 @@
+//! Proof-of-work style searches for the smallest number whose MD5 hash starts with zeroes, like
+//! the AdventCoin mining in day 4.
+//!
+//! The secret key is hashed once and the MD5 state cloned for every number, digests are checked
+//! as raw bytes instead of hex strings, and numbers are tried in parallel chunks.
+use rayon::prelude::*;
+
+/// How many numbers each thread checks at a time
+const CHUNK: usize = 4096;
+
+/// Number of leading zero hex digits in a digest
+pub fn leading_zero_nibbles(digest: &[u8; 16]) -> usize {
+    let mut nibbles = 0;
+    for &b in digest {
+        if b == 0 {
+            nibbles += 2;
+        } else {
+            if b < 0x10 {
+                nibbles += 1;
+            }
+            break;
+        }
+    }
+    nibbles
+}
+
+/// Whether the digest starts with at least `n` zero hex digits. A digest only has 32, so it
+/// never has more.
+pub fn has_zero_prefix(digest: &[u8; 16], n: usize) -> bool {
+    if n > 32 {
+        return false;
+    }
+    let full = n / 2;
+    digest[..full].iter().all(|&b| b == 0) && (n.is_multiple_of(2) || digest[full] < 0x10)
+}
+
+/// A secret key that has already been fed to MD5
+#[derive(Clone)]
+pub struct PrefixHasher {
+    context: md5::Context,
+}
+
+impl PrefixHasher {
+    pub fn new(key: &str) -> Self {
+        let mut context = md5::Context::new();
+        context.consume(key.as_bytes());
+        Self { context }
+    }
+
+    /// The digest of the key followed by `n` in decimal
+    pub fn digest(&self, n: usize) -> [u8; 16] {
+        let mut buf = [0; 20];
+        let mut start = buf.len();
+        let mut rest = n;
+        loop {
+            start -= 1;
+            buf[start] = b'0' + (rest % 10) as u8;
+            rest /= 10;
+            if rest == 0 {
+                break;
+            }
+        }
+        let mut context = self.context.clone();
+        context.consume(&buf[start..]);
+        context.compute().0
+    }
+
+    fn find_in(&self, zeroes: usize, range: std::ops::Range<usize>) -> Option<usize> {
+        range
+            .into_iter()
+            .find(|&n| has_zero_prefix(&self.digest(n), zeroes))
+    }
+
+    /// The smallest number from `start` whose digest has `zeroes` leading zero hex digits,
+    /// checking one number at a time. `None` if `zeroes` is more than a digest has or nothing
+    /// below `usize::MAX` is found.
+    pub fn find_serial(&self, zeroes: usize, start: usize) -> Option<usize> {
+        if zeroes > 32 {
+            return None;
+        }
+        (start..usize::MAX).find(|&n| has_zero_prefix(&self.digest(n), zeroes))
+    }
+
+    /// [`PrefixHasher::find_serial`], spreading the search over every thread. Each round checks
+    /// a run of consecutive chunks and keeps the smallest hit, so the answer is the same.
+    pub fn find(&self, zeroes: usize, start: usize) -> Option<usize> {
+        if zeroes > 32 {
+            return None;
+        }
+        let threads = rayon::current_num_threads().max(1);
+        let mut from = start;
+        loop {
+            let found = (0..threads)
+                .into_par_iter()
+                .filter_map(|t| {
+                    let lo = from.checked_add(t * CHUNK)?;
+                    self.find_in(zeroes, lo..lo.saturating_add(CHUNK))
+                })
+                .min();
+            if found.is_some() {
+                return found;
+            }
+            from = from.checked_add(threads * CHUNK)?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digests_match_md5() {
+        let hasher = PrefixHasher::new("abcdef");
+        for n in [0, 9, 10, 609043, usize::MAX] {
+            let expected = md5::compute(format!("abcdef{}", n)).0;
+            assert_eq!(hasher.digest(n), expected);
+        }
+    }
+
+    #[test]
+    fn zero_prefixes() {
+        let mut digest = [0xff; 16];
+        assert_eq!(leading_zero_nibbles(&digest), 0);
+        assert!(has_zero_prefix(&digest, 0));
+        digest[0] = 0;
+        digest[1] = 0;
+        digest[2] = 0x0a;
+        assert_eq!(leading_zero_nibbles(&digest), 5);
+        assert!(has_zero_prefix(&digest, 5));
+        assert!(!has_zero_prefix(&digest, 6));
+        assert_eq!(leading_zero_nibbles(&[0; 16]), 32);
+        assert!(has_zero_prefix(&[0; 16], 32));
+        assert!(!has_zero_prefix(&[0; 16], 33));
+    }
+
+    #[test]
+    fn published_examples() {
+        assert_eq!(PrefixHasher::new("abcdef").find(5, 0), Some(609043));
+        assert_eq!(PrefixHasher::new("pqrstuv").find(5, 0), Some(1048970));
+    }
+
+    #[test]
+    fn parallel_finds_the_smallest() {
+        let hasher = PrefixHasher::new("abcdef");
+        for start in [0, 1000, 609043, 609044] {
+            assert_eq!(hasher.find(3, start), hasher.find_serial(3, start));
+        }
+    }
+
+    #[test]
+    fn impossible_searches_end() {
+        let hasher = PrefixHasher::new("abcdef");
+        assert_eq!(hasher.find(33, 0), None);
+        assert_eq!(hasher.find_serial(33, 0), None);
+        // running off the end of usize gives up rather than overflowing
+        assert_eq!(hasher.find(32, usize::MAX - 10), None);
+        assert_eq!(hasher.find_serial(32, usize::MAX - 10), None);
+    }
+}
@@ -1,5 +1,9 @@
 #![allow(incomplete_features)]
 #![feature(return_position_impl_trait_in_trait)]
+pub mod circuit;
+pub mod hash_search;
+pub mod parse_error;
+
 use std::fs;
 use std::io::read_to_string;
 
@@ -0,0 +1,89 @@
+//! Parse failures that point at the line and column of the offending input.
+use std::fmt;
+
+/// A parse failure pinned to the line and column of the offending input.
+///
+/// `Display` renders the line with a caret under the column, e.g.
+/// ```text
+/// expected a signal at line 2, column 7
+///   |
+/// 2 | x AND Y -> y
+///   |       ^
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-indexed line of the offending input
+    pub line: usize,
+    /// 1-indexed column (in chars) of the offending input
+    pub column: usize,
+    /// The full text of the offending line
+    pub snippet: String,
+    /// What should have been there
+    pub expected: String,
+}
+
+impl ParseError {
+    /// Locate the byte `offset` inside `input`
+    pub fn at(input: &str, offset: usize, expected: impl Into<String>) -> Self {
+        let offset = offset.min(input.len());
+        let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[offset..]
+            .find('\n')
+            .map_or(input.len(), |i| offset + i);
+
+        Self {
+            line: input[..offset].matches('\n').count() + 1,
+            column: input[line_start..offset].chars().count() + 1,
+            snippet: input[line_start..line_end]
+                .trim_end_matches('\r')
+                .to_string(),
+            expected: expected.into(),
+        }
+    }
+
+    /// Locate `part`, which has to be a slice of `input`
+    pub fn at_slice(input: &str, part: &str, expected: impl Into<String>) -> Self {
+        let offset = (part.as_ptr() as usize).saturating_sub(input.as_ptr() as usize);
+        Self::at(input, offset, expected)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let gutter = " ".repeat(self.line.to_string().len());
+        writeln!(
+            f,
+            "expected {} at line {}, column {}",
+            self.expected, self.line, self.column
+        )?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{} | {}", self.line, self.snippet)?;
+        write!(f, "{gutter} | {}^", " ".repeat(self.column - 1))
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn points_at_offending_column() {
+        let input = "123 -> x\nx AND Y -> y\n";
+        let e = ParseError::at(input, 15, "a signal");
+        assert_eq!((e.line, e.column), (2, 7));
+        assert_eq!(e.snippet, "x AND Y -> y");
+        assert_eq!(
+            e.to_string(),
+            "expected a signal at line 2, column 7\n  |\n2 | x AND Y -> y\n  |       ^"
+        );
+        assert_eq!(ParseError::at_slice(input, &input[15..16], "a signal"), e);
+    }
+
+    #[test]
+    fn end_of_input() {
+        let e = ParseError::at("1\n2", 10, "more");
+        assert_eq!((e.line, e.column), (2, 2));
+    }
+}
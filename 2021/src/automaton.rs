@@ -0,0 +1,422 @@
+//! Cellular automata over rectangular grids.
+//!
+//! A [`Rule`] advances a [`Grid`] by one generation. [`Synchronous`] rules compute every cell
+//! from the previous generation (Game of Life style), [`Cascading`] rules let cells fire and
+//! push their neighbours into firing within the same generation (like the flashing octopuses).
+//! An [`Automaton`] runs a rule and watches for steady states, uniform grids and full
+//! synchronisation, where every cell changes (or fires) at once.
+use std::fmt::Display;
+use std::marker::PhantomData;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighbourhood {
+    /// The 4 orthogonal neighbours
+    VonNeumann,
+    /// All 8 neighbours, including diagonals
+    Moore,
+}
+
+impl Neighbourhood {
+    fn offsets(&self) -> &'static [(isize, isize)] {
+        match self {
+            Neighbourhood::VonNeumann => &[(-1, 0), (0, -1), (0, 1), (1, 0)],
+            Neighbourhood::Moore => &[
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+                (0, -1),
+                (0, 1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// `None` if the rows aren't all the same length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Self> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|r| r.len() != width) {
+            return None;
+        }
+        Some(Self {
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    /// One cell per character, `None` if any character isn't a cell or the rows are ragged
+    pub fn parse(s: &str, cell: impl Fn(char) -> Option<T>) -> Option<Self> {
+        let rows = s
+            .lines()
+            .map(|line| line.chars().map(&cell).collect::<Option<Vec<_>>>())
+            .collect::<Option<Vec<_>>>()?;
+        Self::from_rows(rows)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        (row < self.height && col < self.width).then(|| &self.cells[row * self.width + col])
+    }
+
+    pub fn get_mut(&mut self, row: usize, col: usize) -> Option<&mut T> {
+        (row < self.height && col < self.width).then(|| &mut self.cells[row * self.width + col])
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    /// Positions of the neighbours of `(row, col)` that are inside the grid
+    pub fn neighbours(
+        &self,
+        row: usize,
+        col: usize,
+        neighbourhood: Neighbourhood,
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        neighbourhood.offsets().iter().filter_map(move |(dr, dc)| {
+            let r = row.checked_add_signed(*dr)?;
+            let c = col.checked_add_signed(*dc)?;
+            (r < self.height && c < self.width).then_some((r, c))
+        })
+    }
+
+    fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |r| (0..width).map(move |c| (r, c)))
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.cells.chunks(self.width.max(1)) {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+pub trait Rule {
+    type Cell;
+
+    /// Advance the grid one generation, returning how many cells changed (or fired)
+    fn step(&self, grid: &mut Grid<Self::Cell>) -> usize;
+}
+
+/// Every cell's next state depends only on itself and its neighbours in the previous generation
+pub struct Synchronous<T, F> {
+    neighbourhood: Neighbourhood,
+    update: F,
+    cell: PhantomData<fn(&T) -> T>,
+}
+
+impl<T, F> Synchronous<T, F>
+where
+    F: Fn(&T, &[&T]) -> T,
+{
+    /// `update` gets a cell and its neighbours, and returns the cell's next state
+    pub fn new(neighbourhood: Neighbourhood, update: F) -> Self {
+        Self {
+            neighbourhood,
+            update,
+            cell: PhantomData,
+        }
+    }
+}
+
+impl<T, F> Rule for Synchronous<T, F>
+where
+    T: Clone + PartialEq,
+    F: Fn(&T, &[&T]) -> T,
+{
+    type Cell = T;
+
+    fn step(&self, grid: &mut Grid<T>) -> usize {
+        let next = grid
+            .positions()
+            .map(|(r, c)| {
+                let neighbours = grid
+                    .neighbours(r, c, self.neighbourhood)
+                    .map(|(nr, nc)| grid.get(nr, nc).unwrap())
+                    .collect::<Vec<_>>();
+                (self.update)(grid.get(r, c).unwrap(), &neighbours)
+            })
+            .collect::<Vec<_>>();
+        let changed = next.iter().zip(&grid.cells).filter(|(a, b)| a != b).count();
+        grid.cells = next;
+        changed
+    }
+}
+
+/// Cells that fire affect their neighbours straight away, which may make them fire too. Each
+/// cell fires at most once per generation.
+pub trait Cascade {
+    type Cell;
+
+    fn neighbourhood(&self) -> Neighbourhood {
+        Neighbourhood::Moore
+    }
+
+    /// Applied to every cell at the start of a generation
+    fn tick(&self, cell: &mut Self::Cell);
+
+    fn fires(&self, cell: &Self::Cell) -> bool;
+
+    /// Applied to a cell each time one of its neighbours fires
+    fn receive(&self, cell: &mut Self::Cell);
+
+    /// Applied to every cell that fired once the cascade is over
+    fn settle(&self, cell: &mut Self::Cell);
+}
+
+/// Runs a [`Cascade`] as a [`Rule`], counting the cells that fired
+pub struct Cascading<C>(pub C);
+
+impl<C: Cascade> Rule for Cascading<C> {
+    type Cell = C::Cell;
+
+    fn step(&self, grid: &mut Grid<C::Cell>) -> usize {
+        let rule = &self.0;
+        grid.cells.iter_mut().for_each(|cell| rule.tick(cell));
+
+        let mut fired = grid.cells.iter().map(|c| rule.fires(c)).collect::<Vec<_>>();
+        let mut queue = grid
+            .positions()
+            .filter(|&(r, c)| fired[r * grid.width + c])
+            .collect::<Vec<_>>();
+        while let Some((r, c)) = queue.pop() {
+            let neighbours = grid
+                .neighbours(r, c, rule.neighbourhood())
+                .collect::<Vec<_>>();
+            for (nr, nc) in neighbours {
+                let i = nr * grid.width + nc;
+                rule.receive(&mut grid.cells[i]);
+                if !fired[i] && rule.fires(&grid.cells[i]) {
+                    fired[i] = true;
+                    queue.push((nr, nc));
+                }
+            }
+        }
+
+        let mut count = 0;
+        for (cell, _) in grid.cells.iter_mut().zip(&fired).filter(|(_, f)| **f) {
+            rule.settle(cell);
+            count += 1;
+        }
+        count
+    }
+}
+
+pub struct Automaton<R: Rule> {
+    grid: Grid<R::Cell>,
+    rule: R,
+    generation: usize,
+}
+
+impl<R: Rule> Automaton<R> {
+    pub fn new(grid: Grid<R::Cell>, rule: R) -> Self {
+        Self {
+            grid,
+            rule,
+            generation: 0,
+        }
+    }
+
+    pub fn grid(&self) -> &Grid<R::Cell> {
+        &self.grid
+    }
+
+    /// Number of generations run so far
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Run one generation, returning how many cells changed (or fired)
+    pub fn step(&mut self) -> usize {
+        self.generation += 1;
+        self.rule.step(&mut self.grid)
+    }
+
+    /// Run `generations` generations, returning the total number of changed (or fired) cells
+    pub fn run(&mut self, generations: usize) -> usize {
+        (0..generations).map(|_| self.step()).sum()
+    }
+
+    /// Step until a generation changes nothing, returning that generation. Gives up after
+    /// `limit` generations.
+    pub fn run_until_steady(&mut self, limit: usize) -> Option<usize> {
+        (0..limit).find_map(|_| (self.step() == 0).then_some(self.generation))
+    }
+
+    /// Step until every cell changes (or fires) in the same generation, returning that
+    /// generation. Gives up after `limit` generations.
+    pub fn run_until_synchronised(&mut self, limit: usize) -> Option<usize> {
+        let cells = self.grid.width * self.grid.height;
+        (0..limit).find_map(|_| (self.step() == cells).then_some(self.generation))
+    }
+
+    /// Step until every cell is in the same state, returning that generation. Gives up after
+    /// `limit` generations.
+    pub fn run_until_uniform(&mut self, limit: usize) -> Option<usize>
+    where
+        R::Cell: PartialEq,
+    {
+        (0..limit).find_map(|_| {
+            self.step();
+            self.is_uniform().then_some(self.generation)
+        })
+    }
+
+    pub fn is_uniform(&self) -> bool
+    where
+        R::Cell: PartialEq,
+    {
+        let mut cells = self.grid.cells();
+        let first = cells.next();
+        cells.all(|c| Some(c) == first)
+    }
+
+    /// The current grid followed by the next `generations` generations
+    pub fn frames(&mut self, generations: usize) -> Vec<String>
+    where
+        R::Cell: Display,
+    {
+        let mut frames = vec![self.grid.to_string()];
+        for _ in 0..generations {
+            self.step();
+            frames.push(self.grid.to_string());
+        }
+        frames
+    }
+}
+
+impl<R: Rule> Display for Automaton<R>
+where
+    R::Cell: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Generation {}:", self.generation)?;
+        write!(f, "{}", self.grid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Life {
+        Alive,
+        Dead,
+    }
+
+    impl Display for Life {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", if *self == Life::Alive { '#' } else { '.' })
+        }
+    }
+
+    fn life(s: &str) -> Automaton<impl Rule<Cell = Life>> {
+        let grid = Grid::parse(s, |c| match c {
+            '#' => Some(Life::Alive),
+            '.' => Some(Life::Dead),
+            _ => None,
+        })
+        .unwrap();
+        let rule = Synchronous::new(Neighbourhood::Moore, |cell: &Life, neighbours: &[&Life]| {
+            let alive = neighbours.iter().filter(|n| ***n == Life::Alive).count();
+            match (cell, alive) {
+                (Life::Alive, 2 | 3) | (Life::Dead, 3) => Life::Alive,
+                _ => Life::Dead,
+            }
+        });
+        Automaton::new(grid, rule)
+    }
+
+    #[test]
+    fn blinker_oscillates() {
+        let mut a = life(".....\n..#..\n..#..\n..#..\n.....");
+        let frames = a.frames(2);
+        assert_eq!(frames[1], ".....\n.....\n.###.\n.....\n.....\n");
+        assert_eq!(frames[0], frames[2]);
+        assert_eq!(a.run_until_steady(10), None);
+    }
+
+    #[test]
+    fn settles_into_a_block() {
+        // an L settles into a 2x2 block after one generation
+        let mut a = life("....\n.#..\n.##.\n....");
+        assert_eq!(a.run_until_steady(10), Some(2));
+        assert_eq!(a.grid().to_string(), "....\n.##.\n.##.\n....\n");
+    }
+
+    #[test]
+    fn dying_out_is_uniform() {
+        let mut a = life("#..\n...\n..#");
+        assert!(!a.is_uniform());
+        assert_eq!(a.run_until_uniform(10), Some(1));
+        assert_eq!(a.to_string(), "Generation 1:\n...\n...\n...\n");
+    }
+
+    struct Chain;
+
+    impl Cascade for Chain {
+        type Cell = u8;
+        fn neighbourhood(&self) -> Neighbourhood {
+            Neighbourhood::VonNeumann
+        }
+        fn tick(&self, _: &mut u8) {}
+        fn fires(&self, cell: &u8) -> bool {
+            *cell >= 2
+        }
+        fn receive(&self, cell: &mut u8) {
+            *cell += 1;
+        }
+        fn settle(&self, cell: &mut u8) {
+            *cell = 0;
+        }
+    }
+
+    #[test]
+    fn cascades_fire_once() {
+        let grid = Grid::from_rows(vec![vec![2, 1, 1, 0, 1]]).unwrap();
+        let mut a = Automaton::new(grid, Cascading(Chain));
+        assert_eq!(a.step(), 3);
+        assert_eq!(a.grid().to_string(), "00011\n");
+        assert_eq!(a.run_until_steady(5), Some(2));
+    }
+
+    #[test]
+    fn uniform_is_not_synchronised() {
+        // every cell is the same but none of them ever fire
+        let grid = Grid::from_rows(vec![vec![1, 1], vec![1, 1]]).unwrap();
+        let mut a = Automaton::new(grid, Cascading(Chain));
+        assert!(a.is_uniform());
+        assert_eq!(a.run_until_synchronised(5), None);
+
+        let grid = Grid::from_rows(vec![vec![2, 2], vec![2, 2]]).unwrap();
+        let mut a = Automaton::new(grid, Cascading(Chain));
+        assert_eq!(a.run_until_synchronised(5), Some(1));
+    }
+}
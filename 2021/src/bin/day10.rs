@@ -1,3 +1,4 @@
+use aoc_2021::brackets::{self, Delimiters};
 use lazy_static::lazy_static;
 use std::collections::HashMap;
 use std::fs::File;
@@ -37,87 +38,16 @@ lazy_static! {
     };
 }
 
-const OPENING_CHARS: [char; 4] = ['(', '[', '{', '<'];
-const CLOSING_CHARS: [char; 4] = [')', ']', '}', '>'];
-
-lazy_static! {
-    static ref MATCH: HashMap<char, char> = {
-        let mut map = HashMap::new();
-        for (a, b) in OPENING_CHARS.into_iter().zip(CLOSING_CHARS.into_iter()) {
-            map.insert(a, b);
-        }
-
-        map
-    };
-}
-
 fn completion_score(s: &str, point_table: &HashMap<char, u64>) -> u64 {
-    let mut v: Vec<u64> = s
-        .lines()
-        .filter_map(|line| {
-            let mut stack = vec![];
-            for c in line.chars() {
-                if OPENING_CHARS.contains(&c) {
-                    stack.push(c);
-                    continue;
-                }
-                // Otherwise closing character must match the last character
-                if let Some(&opening) = stack.last() {
-                    // We have matching characters.
-                    if *MATCH.get(&opening).expect("Match should exits") == c {
-                        stack.pop();
-                    } else {
-                        // Mis-matching characters
-                        return None;
-                    }
-                } else {
-                    // Close character with no open character
-                    return None;
-                }
-            }
-
-            let score = stack
-                .into_iter()
-                .map(|c| *MATCH.get(&c).expect("Matching character DNE"))
-                .rfold(0, |score, c| {
-                    score * 5
-                        + point_table
-                            .get(&c)
-                            .expect("Point table has no value for the character")
-                });
-            Some(score)
-        })
-        .collect();
-
-    v.sort_unstable();
-    v[v.len() / 2]
+    let checks = Delimiters::default().check_all(s);
+    brackets::middle_completion_score(&checks, point_table)
+        .expect("Every closing character has points")
+        .expect("No incomplete lines")
 }
 
 fn syntax_error_score(s: &str, point_table: &HashMap<char, u64>) -> u64 {
-    s.lines().fold(0, |score, line| {
-        let mut stack = vec![];
-        for c in line.chars() {
-            if OPENING_CHARS.contains(&c) {
-                stack.push(c);
-                continue;
-            }
-
-            // Otherwise closing character must match the last character
-            if let Some(&opening) = stack.last() {
-                // We have matching characters.
-                if *MATCH.get(&opening).expect("Match should exits") == c {
-                    stack.pop();
-                } else {
-                    // Mis-matching characters
-                    return score + point_table.get(&c).expect("Character not in point table");
-                }
-            } else {
-                // Close character with no open character
-                return score + point_table.get(&c).expect("Character not in point table");
-            }
-        }
-        score
-    })
+    let checks = Delimiters::default().check_all(s);
+    brackets::syntax_error_score(&checks, point_table).expect("Every closing character has points")
 }
 
 #[cfg(test)]
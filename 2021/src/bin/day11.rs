@@ -1,3 +1,4 @@
+use aoc_2021::automaton::*;
 use std::fs::File;
 use std::io::read_to_string;
 
@@ -15,143 +16,72 @@ fn main() {
     assert_eq!(steps, 519);
 }
 
-#[derive(Debug, Eq, PartialEq)]
+/// Octopuses gain energy every step and flash once they go over 9, giving their neighbours
+/// energy too
+struct Octopus;
+
+impl Cascade for Octopus {
+    type Cell = u8;
+    fn tick(&self, energy: &mut u8) {
+        *energy += 1;
+    }
+    fn fires(&self, energy: &u8) -> bool {
+        *energy > 9
+    }
+    fn receive(&self, energy: &mut u8) {
+        *energy += 1;
+    }
+    fn settle(&self, energy: &mut u8) {
+        *energy = 0;
+    }
+}
+
 struct EnergyMap {
-    map: Vec<Vec<u8>>,
+    octopuses: Automaton<Cascading<Octopus>>,
 }
 
 impl EnergyMap {
-    fn flashes_after(&mut self, steps: usize) -> u64 {
-        (0..steps).fold(0, |acc, _| acc + self.step())
+    fn flashes_after(&mut self, steps: usize) -> usize {
+        self.octopuses.run(steps)
     }
 
-    fn steps_to_simul(&mut self) -> u64 {
-        let mut steps = 0;
-        while !self.map.iter().flatten().all(|v| *v == 0) {
-            self.step();
-            steps += 1;
-        }
-        steps
+    fn steps_to_simul(&mut self) -> usize {
+        self.octopuses
+            .run_until_synchronised(usize::MAX)
+            .expect("Octopuses should synchronise")
     }
 
-    fn step(&mut self) -> u64 {
-        // increment all levels by 1
-        let mut flashed = vec![];
-        let mut num_flashed = 0;
-        for (row, inner) in self.map.iter_mut().enumerate() {
-            for (col, value) in inner.iter_mut().enumerate() {
-                *value += 1;
-                if *value > 9 {
-                    flashed.push((row, col));
-                }
-            }
-        }
-
-        // octos w/ levels greater than 9 flash and cause adjectent (and diagonal) levels to increment
-        let mut visited = flashed.clone();
-        for (row, col) in flashed.into_iter() {
-            num_flashed += 1;
-            num_flashed += self.flash(row, col, &mut visited);
-        }
-
-        // any octo that flashed is set to 0
-        for inner in self.map.iter_mut() {
-            for v in inner.iter_mut() {
-                if *v > 9 {
-                    *v = 0;
-                }
-            }
-        }
-        num_flashed
+    #[allow(dead_code)]
+    fn step(&mut self) -> usize {
+        self.octopuses.step()
     }
 
-    fn flash(&mut self, row: usize, col: usize, flashed: &mut Vec<(usize, usize)>) -> u64 {
-        flashed.push((row, col));
-        let adjacent = self.get_adjacent(row, col);
-        let mut num_flashed = 0;
-
-        for (row, col) in adjacent {
-            let value = &mut self.map[row][col];
-            *value += 1;
-            if !flashed.contains(&(row, col)) {
-                if *value > 9 {
-                    num_flashed += 1;
-                    num_flashed += self.flash(row, col, flashed);
-                }
-            }
-        }
-        num_flashed
+    #[allow(dead_code)]
+    fn print(&self) {
+        print!("{}", self.octopuses.grid());
+        println!("=================================")
     }
+}
 
-    fn get_adjacent(&self, row: usize, col: usize) -> Vec<(usize, usize)> {
-        let mut v = vec![];
-        let height = self.map.len();
-        let width = self.map[0].len();
-        // push upper left
-        if row > 0 && col > 0 {
-            v.push((row - 1, col - 1))
-        }
-
-        // push upper right
-        if row > 0 && col < width - 1 {
-            v.push((row - 1, col + 1))
-        }
-
-        // push lower left
-        if row < height - 1 && col > 0 {
-            v.push((row + 1, col - 1))
-        }
-
-        // push lower right
-        if row < height - 1 && col < width - 1 {
-            v.push((row + 1, col + 1))
-        }
-
-        // push upper
-        if row > 0 {
-            v.push((row - 1, col));
-        }
-        // push lower
-        if row < height - 1 {
-            v.push((row + 1, col));
-        }
-
-        // push left
-        if col > 0 {
-            v.push((row, col - 1));
-        }
-        // push right
-        if col < width - 1 {
-            v.push((row, col + 1))
-        }
-        v
+impl PartialEq for EnergyMap {
+    fn eq(&self, other: &Self) -> bool {
+        self.octopuses.grid() == other.octopuses.grid()
     }
+}
 
-    #[allow(dead_code)]
-    fn print(&self) {
-        for inner in self.map.iter() {
-            for v in inner.iter() {
-                print!("{}", v);
-            }
-            println!();
-        }
-        println!("=================================")
+impl std::fmt::Debug for EnergyMap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.octopuses.grid())
     }
 }
 
 impl std::str::FromStr for EnergyMap {
     type Err = ();
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let map = s
-            .lines()
-            .map(|line| {
-                line.chars()
-                    .map(|c| c.to_digit(10).unwrap() as u8)
-                    .collect()
-            })
-            .collect();
-
-        Ok(Self { map })
+        let grid = Grid::parse(s, |c| c.to_digit(10).map(|d| d as u8)).ok_or(())?;
+        Ok(Self {
+            octopuses: Automaton::new(grid, Cascading(Octopus)),
+        })
     }
 }
 
@@ -166,6 +96,12 @@ mod test {
         assert_eq!(ans, 195);
     }
     #[test]
+    fn uniform_energy_waits_for_a_flash() {
+        // all the same from the start, but nothing flashes until they pass 9
+        let mut m: EnergyMap = "111\n111\n111".parse().unwrap();
+        assert_eq!(m.steps_to_simul(), 9);
+    }
+    #[test]
     fn part_one_works() {
         let mut m: EnergyMap = SAMPLE.parse().unwrap();
         let flashes = m.flashes_after(100);
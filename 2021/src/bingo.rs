@@ -0,0 +1,381 @@
+//! Bingo with boards of any size, as played with the giant squid in day 4.
+//!
+//! Boards mark numbers as they are drawn and keep running counts per line, so checking for a win
+//! doesn't need to look at the numbers drawn before. [`Bingo::wins`] plays the whole game and
+//! yields every win in the order it happens.
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+pub type Score = u32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BingoError {
+    Number(ParseIntError),
+    /// The board at this index has rows of different lengths
+    Ragged(usize),
+    /// There are no numbers to draw
+    NoDraws,
+}
+
+impl Display for BingoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BingoError::Number(e) => write!(f, "Invalid number: {}", e),
+            BingoError::Ragged(board) => write!(f, "Board {} has rows of different lengths", board),
+            BingoError::NoDraws => write!(f, "There are no numbers to draw"),
+        }
+    }
+}
+
+impl std::error::Error for BingoError {}
+
+impl From<ParseIntError> for BingoError {
+    fn from(e: ParseIntError) -> Self {
+        BingoError::Number(e)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Board {
+    width: usize,
+    height: usize,
+    numbers: Vec<u32>,
+    /// Where each number is on the board
+    positions: HashMap<u32, Vec<usize>>,
+    marked: Vec<bool>,
+    row_marks: Vec<usize>,
+    col_marks: Vec<usize>,
+    /// Top left to bottom right, then top right to bottom left
+    diagonal_marks: [usize; 2],
+    unmarked_sum: Score,
+    won: bool,
+}
+
+impl Board {
+    /// `None` if the rows aren't all the same length
+    pub fn new(rows: Vec<Vec<u32>>) -> Option<Self> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|r| r.len() != width) {
+            return None;
+        }
+        let numbers = rows.into_iter().flatten().collect::<Vec<_>>();
+        let mut positions = HashMap::<_, Vec<_>>::new();
+        for (i, &n) in numbers.iter().enumerate() {
+            positions.entry(n).or_default().push(i);
+        }
+        Some(Self {
+            width,
+            height,
+            unmarked_sum: numbers.iter().sum(),
+            marked: vec![false; numbers.len()],
+            numbers,
+            positions,
+            row_marks: vec![0; height],
+            col_marks: vec![0; width],
+            diagonal_marks: [0; 2],
+            won: false,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<u32> {
+        (row < self.height && col < self.width).then(|| self.numbers[row * self.width + col])
+    }
+
+    pub fn is_marked(&self, row: usize, col: usize) -> bool {
+        row < self.height && col < self.width && self.marked[row * self.width + col]
+    }
+
+    pub fn has_won(&self) -> bool {
+        self.won
+    }
+
+    pub fn unmarked_sum(&self) -> Score {
+        self.unmarked_sum
+    }
+
+    /// Mark `number` wherever it is on the board, returning true if that completed a row, a
+    /// column or (if `diagonals` is set and the board is square) a diagonal for the first time
+    pub fn mark(&mut self, number: u32, diagonals: bool) -> bool {
+        let Some(positions) = self.positions.get(&number) else {
+            return false;
+        };
+        let mut completed = false;
+        for &i in positions {
+            if self.marked[i] {
+                continue;
+            }
+            self.marked[i] = true;
+            self.unmarked_sum -= number;
+            let (row, col) = (i / self.width, i % self.width);
+            self.row_marks[row] += 1;
+            self.col_marks[col] += 1;
+            completed |= self.row_marks[row] == self.width || self.col_marks[col] == self.height;
+            if diagonals && self.width == self.height {
+                if row == col {
+                    self.diagonal_marks[0] += 1;
+                    completed |= self.diagonal_marks[0] == self.width;
+                }
+                if row + col + 1 == self.width {
+                    self.diagonal_marks[1] += 1;
+                    completed |= self.diagonal_marks[1] == self.width;
+                }
+            }
+        }
+        if completed && !self.won {
+            self.won = true;
+            return true;
+        }
+        false
+    }
+}
+
+impl FromStr for Board {
+    type Err = BingoError;
+    /// One row per line, numbers separated by whitespace
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows = s
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| l.split_whitespace().map(str::parse).collect())
+            .collect::<Result<Vec<_>, _>>()?;
+        Board::new(rows).ok_or(BingoError::Ragged(0))
+    }
+}
+
+impl Display for Board {
+    /// Marked numbers are wrapped in brackets
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = self
+            .numbers
+            .iter()
+            .map(|n| n.to_string().len())
+            .max()
+            .unwrap_or(0);
+        for row in 0..self.height {
+            let cells = (0..self.width)
+                .map(|col| {
+                    let i = row * self.width + col;
+                    if self.marked[i] {
+                        format!("[{:>w$}]", self.numbers[i], w = width)
+                    } else {
+                        format!(" {:>w$} ", self.numbers[i], w = width)
+                    }
+                })
+                .collect::<Vec<_>>();
+            writeln!(f, "{}", cells.join(""))?;
+        }
+        Ok(())
+    }
+}
+
+/// A board winning on a draw
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Win {
+    /// Index of the draw in the list of numbers
+    pub draw: usize,
+    pub number: u32,
+    pub board: usize,
+    pub score: Score,
+}
+
+#[derive(Debug, Clone)]
+pub struct Bingo {
+    draws: Vec<u32>,
+    boards: Vec<Board>,
+    diagonals: bool,
+}
+
+impl Bingo {
+    pub fn new(draws: Vec<u32>, boards: Vec<Board>) -> Self {
+        Self {
+            draws,
+            boards,
+            diagonals: false,
+        }
+    }
+
+    /// Also count completed diagonals on square boards
+    pub fn with_diagonals(mut self, diagonals: bool) -> Self {
+        self.diagonals = diagonals;
+        self
+    }
+
+    pub fn draws(&self) -> &[u32] {
+        &self.draws
+    }
+
+    pub fn boards(&self) -> &[Board] {
+        &self.boards
+    }
+
+    /// Every win in the order it happens, each board winning at most once
+    pub fn wins(&self) -> Wins {
+        Wins {
+            draws: self.draws.clone(),
+            boards: self.boards.clone(),
+            diagonals: self.diagonals,
+            draw: 0,
+            pending: vec![],
+        }
+    }
+
+    pub fn first_win(&self) -> Option<Win> {
+        self.wins().next()
+    }
+
+    pub fn last_win(&self) -> Option<Win> {
+        self.wins().last()
+    }
+}
+
+impl FromStr for Bingo {
+    type Err = BingoError;
+    /// The comma separated draws, then the boards separated by blank lines
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (draws, boards) = s.split_once('\n').unwrap_or((s, ""));
+        if draws.trim().is_empty() {
+            return Err(BingoError::NoDraws);
+        }
+        let draws = draws
+            .trim()
+            .split(',')
+            .map(str::parse)
+            .collect::<Result<_, _>>()?;
+        let boards = boards
+            .split("\n\n")
+            .filter(|b| !b.trim().is_empty())
+            .enumerate()
+            .map(|(i, b)| match b.parse() {
+                Err(BingoError::Ragged(_)) => Err(BingoError::Ragged(i)),
+                board => board,
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self::new(draws, boards))
+    }
+}
+
+/// The game being played, see [`Bingo::wins`]
+pub struct Wins {
+    draws: Vec<u32>,
+    boards: Vec<Board>,
+    diagonals: bool,
+    /// Next draw to make
+    draw: usize,
+    /// Wins from the last draw that haven't been yielded yet, in reverse
+    pending: Vec<Win>,
+}
+
+impl Wins {
+    /// The boards as they are after the draws made so far
+    pub fn boards(&self) -> &[Board] {
+        &self.boards
+    }
+}
+
+impl Iterator for Wins {
+    type Item = Win;
+
+    fn next(&mut self) -> Option<Win> {
+        while self.pending.is_empty() {
+            let &number = self.draws.get(self.draw)?;
+            let draw = self.draw;
+            self.draw += 1;
+            for (board, b) in self.boards.iter_mut().enumerate().rev() {
+                if !b.has_won() && b.mark(number, self.diagonals) {
+                    self.pending.push(Win {
+                        draw,
+                        number,
+                        board,
+                        score: b.unmarked_sum() * number,
+                    });
+                }
+            }
+        }
+        self.pending.pop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wins_in_order() {
+        let bingo: Bingo = "1,2,3,4,5,6\n\n1 2\n3 9\n\n9 4\n5 6\n\n2 6\n1 4"
+            .parse()
+            .unwrap();
+        let wins = bingo.wins().collect::<Vec<_>>();
+        assert_eq!(
+            wins,
+            vec![
+                Win {
+                    draw: 1,
+                    number: 2,
+                    board: 0,
+                    score: 24
+                },
+                Win {
+                    draw: 1,
+                    number: 2,
+                    board: 2,
+                    score: 20
+                },
+                Win {
+                    draw: 5,
+                    number: 6,
+                    board: 1,
+                    score: 54
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn diagonals_and_odd_sizes() {
+        let board = "1 2 3\n4 5 6\n7 8 9";
+        let draws = "3,5,7";
+        let plain: Bingo = format!("{}\n\n{}", draws, board).parse().unwrap();
+        assert_eq!(plain.first_win(), None);
+        let diagonal = plain.with_diagonals(true);
+        assert_eq!(diagonal.first_win().unwrap().score, 30 * 7);
+
+        let wide: Bingo = "1,2,3\n\n1 2 3 4\n5 6 7 8".parse().unwrap();
+        assert_eq!(wide.first_win(), None);
+        let wide: Bingo = "1,5\n\n1 2 3 4\n5 6 7 8".parse().unwrap();
+        assert_eq!(wide.first_win().unwrap().score, 30 * 5);
+    }
+
+    #[test]
+    fn marking_is_incremental() {
+        let mut board: Board = "1 2\n3 4".parse().unwrap();
+        assert!(!board.mark(1, false));
+        assert!(!board.mark(1, false));
+        assert!(!board.mark(4, false));
+        assert_eq!(board.unmarked_sum(), 5);
+        assert!(board.mark(3, false));
+        // only the first completed line is a win
+        assert!(!board.mark(2, false));
+        assert_eq!(board.to_string(), "[1][2]\n[3][4]\n");
+    }
+
+    #[test]
+    fn bad_input() {
+        assert_eq!("".parse::<Bingo>().unwrap_err(), BingoError::NoDraws);
+        assert_eq!(
+            "1\n\n1 2\n3 4\n\n1 2\n3".parse::<Bingo>().unwrap_err(),
+            BingoError::Ragged(1)
+        );
+        assert!("1,x".parse::<Bingo>().is_err());
+    }
+}
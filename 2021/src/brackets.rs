@@ -0,0 +1,203 @@
+//! Checking that delimiters are balanced, like the navigation subsystem chunks in day 10.
+//!
+//! [`Delimiters::check`] says whether a line is valid, corrupted (and where) or incomplete (and
+//! how to complete it). Turning those results into points is left to the scoring functions, so
+//! different puzzles can weigh them however they like.
+use std::collections::HashMap;
+use std::fmt::Display;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Check {
+    Valid,
+    /// A closing character that doesn't match the last open one
+    Corrupted {
+        /// Character index in the line
+        position: usize,
+        /// The closing character that was needed, `None` if nothing was open
+        expected: Option<char>,
+        found: char,
+    },
+    /// Lines that end with chunks still open
+    Incomplete {
+        /// The closing characters that would complete the line, in order
+        completion: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delimiters {
+    /// (opening, closing)
+    pairs: Vec<(char, char)>,
+}
+
+impl Default for Delimiters {
+    /// `()`, `[]`, `{}` and `<>`
+    fn default() -> Self {
+        Self::new(&[('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')])
+    }
+}
+
+impl Delimiters {
+    pub fn new(pairs: &[(char, char)]) -> Self {
+        Self {
+            pairs: pairs.to_vec(),
+        }
+    }
+
+    pub fn closing_for(&self, open: char) -> Option<char> {
+        self.pairs.iter().find(|p| p.0 == open).map(|p| p.1)
+    }
+
+    pub fn is_closing(&self, c: char) -> bool {
+        self.pairs.iter().any(|p| p.1 == c)
+    }
+
+    /// Check a single line, characters that aren't delimiters are skipped
+    pub fn check(&self, line: &str) -> Check {
+        let mut stack = vec![];
+        for (position, c) in line.chars().enumerate() {
+            if let Some(close) = self.closing_for(c) {
+                stack.push(close);
+            } else if self.is_closing(c) {
+                match stack.pop() {
+                    Some(expected) if expected == c => {}
+                    expected => {
+                        return Check::Corrupted {
+                            position,
+                            expected,
+                            found: c,
+                        }
+                    }
+                }
+            }
+        }
+        if stack.is_empty() {
+            Check::Valid
+        } else {
+            Check::Incomplete {
+                completion: stack.into_iter().rev().collect(),
+            }
+        }
+    }
+
+    pub fn check_all(&self, input: &str) -> Vec<Check> {
+        input.lines().map(|l| self.check(l)).collect()
+    }
+}
+
+/// A character that the points table has no score for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Unscored(pub char);
+
+impl Display for Unscored {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "There are no points for '{}'", self.0)
+    }
+}
+
+impl std::error::Error for Unscored {}
+
+fn points_for(points: &HashMap<char, u64>, c: char) -> Result<u64, Unscored> {
+    points.get(&c).copied().ok_or(Unscored(c))
+}
+
+/// Sum of the points for the first illegal character of every corrupted line
+pub fn syntax_error_score(checks: &[Check], points: &HashMap<char, u64>) -> Result<u64, Unscored> {
+    checks
+        .iter()
+        .filter_map(|c| match c {
+            Check::Corrupted { found, .. } => Some(points_for(points, *found)),
+            _ => None,
+        })
+        .sum()
+}
+
+/// Score a completion string, multiplying by 5 before adding each character's points
+pub fn completion_score(completion: &str, points: &HashMap<char, u64>) -> Result<u64, Unscored> {
+    completion
+        .chars()
+        .try_fold(0, |score, c| Ok(score * 5 + points_for(points, c)?))
+}
+
+/// The middle completion score of the incomplete lines, `None` if there aren't any
+pub fn middle_completion_score(
+    checks: &[Check],
+    points: &HashMap<char, u64>,
+) -> Result<Option<u64>, Unscored> {
+    let mut scores = checks
+        .iter()
+        .filter_map(|c| match c {
+            Check::Incomplete { completion } => Some(completion_score(completion, points)),
+            _ => None,
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    scores.sort_unstable();
+    Ok(scores.get(scores.len() / 2).copied())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn structured_results() {
+        let d = Delimiters::default();
+        assert_eq!(d.check("([]{<>})"), Check::Valid);
+        assert_eq!(
+            d.check("{([(<{}[<>[]}>{[]{[(<()>"),
+            Check::Corrupted {
+                position: 12,
+                expected: Some(']'),
+                found: '}'
+            }
+        );
+        assert_eq!(
+            d.check(")"),
+            Check::Corrupted {
+                position: 0,
+                expected: None,
+                found: ')'
+            }
+        );
+        assert_eq!(
+            d.check("[({(<(())[]>[[{[]{<()<>>"),
+            Check::Incomplete {
+                completion: "}}]])})]".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn custom_pairs() {
+        let d = Delimiters::new(&[('/', '\\'), ('(', ')')]);
+        assert_eq!(d.check("/(x)\\"), Check::Valid);
+        // `[` isn't a delimiter any more
+        assert_eq!(
+            d.check("/(]"),
+            Check::Incomplete {
+                completion: ")\\".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn scoring() {
+        let points = HashMap::from([(')', 1), (']', 2), ('}', 3), ('>', 4)]);
+        assert_eq!(completion_score("])}>", &points), Ok(294));
+        let checks = Delimiters::default().check_all("(\n<\n[\n)");
+        assert_eq!(middle_completion_score(&checks, &points), Ok(Some(2)));
+        assert_eq!(syntax_error_score(&checks, &points), Ok(1));
+    }
+
+    #[test]
+    fn missing_points() {
+        let points = HashMap::from([(')', 1)]);
+        let checks = Delimiters::new(&[('(', ')'), ('/', '\\')]).check_all("(/\n\\\n()");
+        assert_eq!(syntax_error_score(&checks, &points), Err(Unscored('\\')));
+        assert_eq!(
+            middle_completion_score(&checks, &points),
+            Err(Unscored('\\'))
+        );
+        assert_eq!(completion_score(")", &points), Ok(1));
+    }
+}
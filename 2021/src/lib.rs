@@ -1 +1,3 @@
-
+pub mod automaton;
+pub mod bingo;
+pub mod brackets;
@@ -1,7 +1,6 @@
 #![feature(test)]
 extern crate test;
-use anyhow::{Context, Result};
-use aoc_2022::*;
+use aoc_2022::{crates::*, *};
 
 const DAY: u8 = 5;
 
@@ -15,60 +14,18 @@ fn main() {
     );
 }
 
-type Stack = Vec<char>;
-type Instruction = (u32, usize, usize);
-
-fn parse(s: &str) -> Result<(Vec<Stack>, Vec<Instruction>)> {
-    let begin_instructions_idx = s
-        .find("move")
-        .with_context(|| "Failed to find the begining of the instructions")?;
-
-    let instructions_slice = &s[begin_instructions_idx..];
-    let instructions = instructions_slice
-        .lines()
-        .map(|line| -> Result<_> {
-            let v = line.split_whitespace().collect::<Vec<_>>();
-            Ok((
-                v[1].parse::<u32>()?,
-                v[3].parse::<usize>()? - 1,
-                v[5].parse::<usize>()? - 1,
-            ))
-        })
-        .collect::<Result<_, _>>()?;
-
-    let stacks_slice = &s[..begin_instructions_idx - 1];
-    let stack_lines = stacks_slice.lines().collect::<Vec<_>>();
-
-    let n_stacks = stacks_slice[stacks_slice.len() - 3..stacks_slice.len() - 2].parse()?;
-    let mut stacks = vec![vec![]; n_stacks];
-
-    for &row in stack_lines[..stack_lines.len() - 1].iter().rev() {
-        for (stack_idx, slice_idx) in (1..row.len()).step_by(4).enumerate() {
-            let c = row.as_bytes()[slice_idx] as char;
-            if c.is_alphabetic() {
-                stacks[stack_idx].push(c)
-            }
-        }
-    }
-
-    Ok((stacks, instructions))
+fn rearrange(input: &str, strategy: &impl Strategy) -> String {
+    let (mut stacks, moves) = parse_procedure(input).expect("Malformed input");
+    stacks
+        .run(&moves, strategy)
+        .expect("There should be a box in the input stack to move");
+    stacks.tops()
 }
 
 pub mod p1 {
     use super::*;
     pub fn solve(input: &str) -> String {
-        let (mut stacks, instructions) = parse(input).expect("Malformed input");
-
-        for instruction in instructions {
-            for _ in 0..instruction.0 {
-                let m = stacks[instruction.1]
-                    .pop()
-                    .expect("There should be a box in the input stack to move");
-                stacks[instruction.2].push(m);
-            }
-        }
-
-        stacks.into_iter().filter_map(|mut c| c.pop()).collect()
+        rearrange(input, &OneAtATime)
     }
 }
 
@@ -76,19 +33,7 @@ pub mod p2 {
     use super::*;
 
     pub fn solve(input: &str) -> String {
-        let (mut stacks, instructions) = parse(input).expect("Malformed input");
-        for instruction in instructions {
-            let mut ms = (0..instruction.0)
-                .map(|_| {
-                    stacks[instruction.1]
-                        .pop()
-                        .expect("There should be a box to move of the stack")
-                })
-                .collect::<Vec<_>>();
-            stacks[instruction.2].extend(ms.drain(..).rev())
-        }
-
-        stacks.into_iter().filter_map(|mut c| c.pop()).collect()
+        rearrange(input, &Batch)
     }
 }
 
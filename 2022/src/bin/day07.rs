@@ -1,9 +1,6 @@
-#![feature(test, absolute_path)]
+#![feature(test)]
 extern crate test;
-use std::{collections::HashMap, path::PathBuf};
-
-use anyhow::{bail, Context, Result};
-use aoc_2022::*;
+use aoc_2022::{filesystem::*, *};
 
 const DAY: u8 = 7;
 
@@ -17,58 +14,19 @@ fn main() {
     );
 }
 
-fn parse(input: &str) -> Result<HashMap<PathBuf, usize>> {
-    input
-        .lines()
-        .filter(|l| !l.starts_with("dir") && !l.starts_with("$ ls"))
-        .try_fold(
-            (HashMap::new(), PathBuf::new()),
-            |(mut map, mut cwd), line| {
-                if line.starts_with("$ cd") {
-                    let new_dir = line.get(5..).with_context(|| {
-                        format!("cd command has no target directory. Line: \"{}\"", line)
-                    })?;
-
-                    if new_dir.trim().is_empty() {
-                        bail!("cd command target should not be blank. Line: \"{}\"", line)
-                    }
-
-                    if new_dir == ".." {
-                        cwd.pop();
-                    } else {
-                        cwd = cwd.join(new_dir);
-                    }
-                } else {
-                    let bytes = line
-                        .split_once(" ")
-                        .with_context(|| {
-                            format!(
-                                "File entry should have bytes and file name. Line: \"{}\"",
-                                line
-                            )
-                        })?
-                        .0
-                        .parse::<usize>()
-                        .with_context(|| {
-                            format!("Bytes should be an integer. Line: \"{}\"", line)
-                        })?;
-
-                    for ancestor in cwd.ancestors() {
-                        *map.entry(ancestor.to_path_buf()).or_default() += bytes;
-                    }
-                }
-                Ok((map, cwd))
-            },
-        )
-        .map(|x| x.0)
+/// Recursive size of every directory
+fn dir_sizes(input: &str) -> Vec<usize> {
+    let fs = FileSystem::from_transcript(input).expect("Failed to parse input");
+    let sizes = fs.sizes();
+    fs.dirs(FileSystem::ROOT).map(|d| sizes[d]).collect()
 }
 
 pub mod p1 {
     use super::*;
     pub fn solve(input: &str) -> usize {
-        let map = parse(input).expect("Failed to parse input");
-        map.values()
-            .filter_map(|&bytes| if bytes <= 100_000 { Some(bytes) } else { None })
+        dir_sizes(input)
+            .into_iter()
+            .filter(|&bytes| bytes <= 100_000)
             .sum()
     }
 }
@@ -77,15 +35,13 @@ pub mod p2 {
     use super::*;
     pub fn solve(input: &str) -> usize {
         const NEEDED_SPACE: usize = 30_000_000;
-        let map = parse(input).expect("Failed to parse input");
-        let used_space = map
-            .get(&PathBuf::from(r"/"))
-            .expect("Root dir should exist");
+        let sizes = dir_sizes(input);
+        let used_space = sizes[0];
 
         let must_free = used_space - NEEDED_SPACE;
 
-        map.values()
-            .cloned()
+        sizes
+            .into_iter()
             .filter(|bytes| *bytes >= must_free)
             .min()
             .expect("An answer should exist")
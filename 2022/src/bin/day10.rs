@@ -1,44 +1,30 @@
 #![feature(test)]
 extern crate test;
-use std::{fmt::Display, str::FromStr};
+use std::str::FromStr;
 
 use anyhow::{bail, Context, Error, Result};
-use aoc_2022::*;
+use aoc_2022::{ocr, parse_error::ParseError, vm::*, *};
 
 const DAY: u8 = 10;
 
-fn main() {
+fn main() -> Result<()> {
     let input = &read_input(DAY);
     println!(
-        "Day {:0>2}: Part 1 answer = {}, Part 2 is a rendered drawing",
+        "Day {:0>2}: Part 1 answer = {}, Part 2 answer = {}",
         DAY,
-        p1::solve(input)
+        p1::solve(input)?,
+        p2::solve(input)?
     );
-    p2::solve(input);
+    Ok(())
 }
 
 #[derive(Debug)]
-struct Instruction {
-    instruction: _Instruction,
-    line_number: usize,
-}
-
-#[derive(Debug)]
-enum _Instruction {
+enum Instruction {
     Noop,
     Add(isize),
 }
 
-impl Instruction {
-    fn cycles(&self) -> u8 {
-        match self.instruction {
-            _Instruction::Noop => 1,
-            _Instruction::Add(_) => 2,
-        }
-    }
-}
-
-impl FromStr for _Instruction {
+impl FromStr for Instruction {
     type Err = Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let context = || format!("Could not parse instruction from: '{}'", s);
@@ -51,148 +37,70 @@ impl FromStr for _Instruction {
     }
 }
 
-fn parse(input: &str) -> Result<Vec<Instruction>> {
-    input
-        .lines()
-        .enumerate()
-        .map(|(ln, l)| {
-            Ok(Instruction {
-                instruction: l.parse::<_Instruction>()?,
-                line_number: ln + 1,
-            })
-        })
-        .collect()
-}
-
-#[derive(Debug)]
-struct Cpu {
-    cycle: usize,
-    register: isize,
-    ip: usize,
-    insutruction_cylces_remaining: u8,
-    instructions: Vec<Instruction>,
-    done: bool,
-}
-
-impl Display for Cpu {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "                       Cycle: {}", self.cycle)?;
-        writeln!(f, "                    Register: {}", self.register)?;
-        writeln!(
-            f,
-            "             Signal Strength: {}",
-            self.signal_strength()
-        )?;
-        writeln!(
-            f,
-            "                 Instruction: {:?}",
-            self.instructions[self.ip].instruction
-        )?;
-        writeln!(
-            f,
-            "      Insruction Line Number: {:?}",
-            self.instructions[self.ip].line_number
-        )?;
-        writeln!(
-            f,
-            "Instruction Cycles Remaining: {}",
-            self.insutruction_cylces_remaining
-        )?;
-        Ok(())
-    }
-}
-
-impl Cpu {
-    fn new(instructions: Vec<Instruction>) -> Self {
-        Self {
-            cycle: 0,
-            register: 1,
-            ip: 0,
-            insutruction_cylces_remaining: instructions[0].cycles(),
-            instructions,
-            done: false,
-        }
-    }
-
-    fn advance_ip(&mut self) -> Result<()> {
-        if self.ip + 1 >= self.instructions.len() {
-            bail!("No remaining instructions")
-        }
-        self.ip += 1;
-        self.insutruction_cylces_remaining = self.instructions[self.ip].cycles();
-        Ok(())
-    }
-
-    fn tick(&mut self) -> Result<()> {
-        if self.done {
-            bail!("No more instructions to execute");
-        }
-
-        self.cycle += 1;
-        self.insutruction_cylces_remaining -= 1;
-        if self.insutruction_cylces_remaining > 0 {
-            return Ok(());
-        }
-
-        match self.instructions[self.ip].instruction {
-            _Instruction::Noop => {}
-            _Instruction::Add(n) => self.register += n,
-        }
+impl InstructionSet for Instruction {
+    /// The X register
+    type Registers = isize;
 
-        if self.advance_ip().is_err() {
-            self.done = true;
+    fn cycles(&self) -> usize {
+        match self {
+            Instruction::Noop => 1,
+            Instruction::Add(_) => 2,
         }
-        Ok(())
     }
 
-    fn tick_n(&mut self, ticks: usize) -> Result<()> {
-        for _ in 0..ticks {
-            self.tick()
-                .with_context(|| format!("Tick {} times", ticks))?;
+    fn execute(&self, x: &mut isize) -> Flow {
+        if let Instruction::Add(n) = self {
+            *x += n;
         }
-        println!("{}", self);
-        Ok(())
+        Flow::Next
     }
+}
 
-    fn signal_strength(&self) -> isize {
-        self.register * self.cycle as isize
-    }
+fn parse(input: &str) -> Result<Vm<Instruction>, ParseError> {
+    Ok(Vm::new(parse_program(input)?, 1))
 }
 
 pub mod p1 {
     use super::*;
-    pub fn solve(input: &str) -> isize {
-        let instructions = parse(input).unwrap();
-        let mut cpu = Cpu::new(instructions);
-        cpu.tick_n(20).unwrap();
-        (0..5).fold(cpu.signal_strength(), |acc, _| {
-            cpu.tick_n(40).unwrap();
-            acc + cpu.signal_strength()
-        })
+    /// The cycle number times the X register during every 40th cycle from the 20th
+    pub fn solve(input: &str) -> Result<isize> {
+        let mut cpu = parse(input)?;
+        (20..=220)
+            .step_by(40)
+            .map(|cycle| {
+                let during = cpu.during(cycle)?;
+                Ok(cycle as isize * during.registers)
+            })
+            .sum()
     }
 }
 
 pub mod p2 {
     use super::*;
-    pub fn solve(input: &str) -> String {
-        let instructions = parse(input).unwrap();
-        let mut cpu = Cpu::new(instructions);
-        let mut display = String::new();
+    /// Read the letters drawn on the CRT
+    pub fn solve(input: &str) -> Result<String> {
+        ocr::recognize(&render(input)?)
+    }
 
-        for _ in 0..6 {
-            for col in 0..40 {
-                let sprite_pos = cpu.register;
+    /// Draw the CRT, `#` for lit pixels and `.` for dark ones
+    pub fn render(input: &str) -> Result<String> {
+        let mut cpu = parse(input)?;
+        let mut display = String::new();
 
-                if col == sprite_pos || col == sprite_pos - 1 || col == sprite_pos + 1 {
-                    display.push('#');
-                } else {
-                    display.push('.');
-                }
-                cpu.tick().unwrap();
+        // the screen is 6 rows of 40 pixels
+        cpu.add_breakpoint(Breakpoint::Cycle(241));
+        cpu.run_with(|during| {
+            let col = (during.cycle - 1) as isize % 40;
+            if (during.registers - 1..=during.registers + 1).contains(&col) {
+                display.push('#');
+            } else {
+                display.push('.');
             }
-            display.push('\n');
-        }
-        display
+            if col == 39 {
+                display.push('\n');
+            }
+        });
+        Ok(display)
     }
 }
 
@@ -202,22 +110,30 @@ mod day10_tests {
 
     const SAMPLE: &str = include_str!("../../inputs/day10/sample.txt");
 
+    #[test]
+    fn malformed_input_reports_position() {
+        let e = p1::solve("noop\naddx 3\naddx y\n").unwrap_err();
+        let e = e.downcast_ref::<ParseError>().unwrap();
+        assert_eq!((e.line, e.column), (3, 1));
+        assert!(p1::solve("noop\n").is_err());
+    }
+
     #[test]
     fn p1_sample() {
-        assert_eq!(p1::solve(SAMPLE), 13360)
+        assert_eq!(p1::solve(SAMPLE).unwrap(), 13140)
     }
 
     #[test]
     fn p1_input() {
         let input = &read_input(DAY);
-        assert_eq!(p1::solve(input), 15260)
+        assert_eq!(p1::solve(input).unwrap(), 15260)
     }
 
     #[test]
     fn p2_sample() {
-        println!("{}", p2::solve(SAMPLE));
+        println!("{}", p2::render(SAMPLE).unwrap());
         assert_eq!(
-            p2::solve(SAMPLE),
+            p2::render(SAMPLE).unwrap(),
             include_str!("../../inputs/day10/expected-p2-sample.txt")
         );
     }
@@ -225,11 +141,12 @@ mod day10_tests {
     #[test]
     fn p2_input() {
         let input = &read_input(DAY);
-        println!("{}", p2::solve(input));
+        println!("{}", p2::render(input).unwrap());
         assert_eq!(
-            p2::solve(input),
+            p2::render(input).unwrap(),
             include_str!("../../inputs/day10/expected-p2-input.txt")
         );
+        assert_eq!(p2::solve(input).unwrap(), "PGHFGLUG");
     }
 }
 
@@ -1,8 +1,6 @@
-#![feature(test, array_windows)]
+#![feature(test)]
 extern crate test;
-use std::collections::HashSet;
-
-use aoc_2022::*;
+use aoc_2022::{sand::*, vector::Vec2, *};
 
 const DAY: u8 = 14;
 
@@ -16,177 +14,22 @@ fn main() {
     );
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
-struct Point {
-    x: usize,
-    y: usize,
-}
-
-impl Point {
-    fn new(x: usize, y: usize) -> Self {
-        Self { x, y }
-    }
-
-    fn down(&self) -> Self {
-        Self::new(self.x, self.y + 1)
-    }
-    fn down_left(&self) -> Self {
-        Self::new(self.x - 1, self.y + 1)
-    }
-    fn down_right(&self) -> Self {
-        Self::new(self.x + 1, self.y + 1)
-    }
-
-    fn falls_on_line(&self, line: &[Point]) -> bool {
-        line.array_windows::<2>().any(|[startpoint, endpoint]| {
-            if startpoint.x == endpoint.x {
-                let ymin = startpoint.y.min(endpoint.y);
-                let ymax = startpoint.y.max(endpoint.y);
-                self.x == startpoint.x && ymin <= self.y && self.y <= ymax
-            } else if startpoint.y == endpoint.y {
-                let xmin = startpoint.x.min(endpoint.x);
-                let xmax = startpoint.x.max(endpoint.x);
-                self.y == startpoint.y && xmin <= self.x && self.x <= xmax
-            } else {
-                panic!("Lines should always be either horizontal or vertical")
-            }
-        })
-    }
-}
-
-#[derive(Debug)]
-struct Game {
-    obstacles: Vec<Vec<Point>>,
-    sand: HashSet<Point>,
-    start_point: Point,
-    lowest_obstacle: usize,
-}
-
-impl Game {
-    fn new(obstacles: Vec<Vec<Point>>, floor: bool) -> Self {
-        let mut lowest_obstacle = obstacles
-            .iter()
-            .map(|line| {
-                line.iter()
-                    .map(|p| p.y)
-                    .max()
-                    .expect("Obstacles is non-empty")
-            })
-            .max()
-            .expect("Obstacles is non-empty");
-
-        if floor {
-            lowest_obstacle += 2;
-        }
-
-        Self {
-            obstacles,
-            start_point: Point::new(500, 0),
-            sand: Default::default(),
-            lowest_obstacle,
-        }
-    }
-
-    fn is_blocked(&self, point: &Point) -> bool {
-        self.sand.contains(point) || (self.obstacles.iter().any(|line| point.falls_on_line(line)))
-    }
-
-    fn is_blocked_by_floor(&self, point: &Point) -> bool {
-        point.y + 1 == self.lowest_obstacle
-    }
-
-    fn drop_sand(&mut self) -> bool {
-        if self.is_blocked(&self.start_point) {
-            return false;
-        }
-
-        loop {
-            if self.start_point.y > self.lowest_obstacle {
-                return false;
-            }
-
-            if !self.is_blocked(&self.start_point.down()) {
-                self.start_point = self.start_point.down();
-            } else if !self.is_blocked(&self.start_point.down_left()) {
-                self.start_point = self.start_point.down_left();
-            } else if !self.is_blocked(&self.start_point.down_right()) {
-                self.start_point = self.start_point.down_right();
-            } else {
-                let p = std::mem::replace(&mut self.start_point, Point::new(500, 0));
-                self.sand.insert(p);
-                return true;
-            }
-        }
-    }
-
-    fn drop_sand_with_floor(&mut self) -> bool {
-        if self.is_blocked(&self.start_point) {
-            return false;
-        }
-
-        loop {
-            if self.is_blocked_by_floor(&self.start_point) {
-                let p = std::mem::replace(&mut self.start_point, Point::new(500, 0));
-                self.sand.insert(p);
-                return true;
-            }
-
-            if !self.is_blocked(&self.start_point.down()) {
-                self.start_point = self.start_point.down();
-            } else if !self.is_blocked(&self.start_point.down_left()) {
-                self.start_point = self.start_point.down_left();
-            } else if !self.is_blocked(&self.start_point.down_right()) {
-                self.start_point = self.start_point.down_right();
-            } else {
-                let p = std::mem::replace(&mut self.start_point, Point::new(500, 0));
-                self.sand.insert(p);
-                return true;
-            }
-        }
-    }
-
-    fn play(&mut self) {
-        while self.drop_sand() {}
-    }
-
-    fn play_with_floor(&mut self) {
-        while self.drop_sand_with_floor() {}
-    }
-}
-
-fn parse(input: &str) -> Vec<Vec<Point>> {
-    input
-        .lines()
-        .map(|line| {
-            line.split(" -> ")
-                .map(|point_slice| {
-                    let (x, y) = point_slice
-                        .split_once(',')
-                        .expect("Point should have a comma");
-                    Point::new(x.parse().unwrap(), y.parse().unwrap())
-                })
-                .collect()
-        })
-        .collect()
+fn parse(input: &str) -> Cave {
+    let rock = parse_rock(input).expect("Rock paths should parse");
+    Cave::new(rock, Vec2::new(500, 0)).resume_from_path(true)
 }
 
 pub mod p1 {
     use super::*;
     pub fn solve(input: &str) -> usize {
-        let obstacles = parse(input);
-        let mut game = Game::new(obstacles, false);
-        game.play();
-        game.sand.len()
+        parse(input).fill()
     }
 }
 
 pub mod p2 {
     use super::*;
     pub fn solve(input: &str) -> usize {
-        let obstacles = parse(input);
-        let mut game = Game::new(obstacles, true);
-        game.play_with_floor();
-        game.sand.len()
+        parse(input).with_bottom(Bottom::Floor).fill()
     }
 }
 
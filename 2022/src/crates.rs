@@ -0,0 +1,280 @@
+//! Stacks of crates rearranged by a crane, like the supply stacks in day 5.
+//!
+//! [`Stacks`] parses the puzzle's drawing and prints it back in the same format. How a crane
+//! moves several crates at once is up to the [`Strategy`], and moves that take more crates than
+//! a stack holds are errors rather than panics.
+use anyhow::{bail, ensure, Context, Error, Result};
+use std::fmt::Display;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub count: usize,
+    /// 0-indexed source stack
+    pub from: usize,
+    /// 0-indexed destination stack
+    pub to: usize,
+}
+
+impl FromStr for Move {
+    type Err = Error;
+    /// `move 1 from 2 to 1`, with the puzzle's 1-indexed stacks
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words = s.split_whitespace().collect::<Vec<_>>();
+        let ["move", count, "from", from, "to", to] = words[..] else {
+            bail!("Expected 'move <n> from <stack> to <stack>', got '{}'", s);
+        };
+        let stack = |n: &str| -> Result<usize> {
+            let n = n.parse::<usize>()?;
+            ensure!(n > 0, "Stacks are numbered from 1");
+            Ok(n - 1)
+        };
+        Ok(Self {
+            count: count.parse()?,
+            from: stack(from)?,
+            to: stack(to)?,
+        })
+    }
+}
+
+/// How a crane moves crates from one stack to another
+pub trait Strategy {
+    /// Take `count` crates off the top of `stack`, in the order they should be pushed onto the
+    /// destination. `stack` always has at least `count` crates.
+    fn take(&self, stack: &mut Vec<char>, count: usize) -> Vec<char>;
+}
+
+/// Crates are moved one at a time, so they end up reversed
+pub struct OneAtATime;
+
+impl Strategy for OneAtATime {
+    fn take(&self, stack: &mut Vec<char>, count: usize) -> Vec<char> {
+        let mut taken = stack.split_off(stack.len() - count);
+        taken.reverse();
+        taken
+    }
+}
+
+/// Crates are all moved together, keeping their order
+pub struct Batch;
+
+impl Strategy for Batch {
+    fn take(&self, stack: &mut Vec<char>, count: usize) -> Vec<char> {
+        stack.split_off(stack.len() - count)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stacks {
+    /// Bottom crate first
+    stacks: Vec<Vec<char>>,
+}
+
+impl Stacks {
+    pub fn new(stacks: Vec<Vec<char>>) -> Self {
+        Self { stacks }
+    }
+
+    pub fn stacks(&self) -> &[Vec<char>] {
+        &self.stacks
+    }
+
+    /// The crate on top of every non-empty stack
+    pub fn tops(&self) -> String {
+        self.stacks.iter().filter_map(|s| s.last()).collect()
+    }
+
+    pub fn apply(&mut self, mv: Move, strategy: &impl Strategy) -> Result<()> {
+        let n = self.stacks.len();
+        ensure!(mv.from < n, "There is no stack {}", mv.from + 1);
+        ensure!(mv.to < n, "There is no stack {}", mv.to + 1);
+        let held = self.stacks[mv.from].len();
+        ensure!(
+            held >= mv.count,
+            "Can't move {} crates from stack {}, it only has {}",
+            mv.count,
+            mv.from + 1,
+            held
+        );
+        let crates = strategy.take(&mut self.stacks[mv.from], mv.count);
+        self.stacks[mv.to].extend(crates);
+        Ok(())
+    }
+
+    pub fn run(&mut self, moves: &[Move], strategy: &impl Strategy) -> Result<()> {
+        self.run_with(moves, strategy, |_, _| {})
+    }
+
+    /// [`Stacks::run`], handing `hook` each move along with the stacks after it
+    pub fn run_with(
+        &mut self,
+        moves: &[Move],
+        strategy: &impl Strategy,
+        mut hook: impl FnMut(&Move, &Stacks),
+    ) -> Result<()> {
+        for (i, mv) in moves.iter().enumerate() {
+            self.apply(*mv, strategy)
+                .with_context(|| format!("Move {}", i + 1))?;
+            hook(mv, self);
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Stacks {
+    type Err = Error;
+    /// The drawing of the stacks, ending with the line of stack numbers. Crates are matched to
+    /// the nearest number so lines may be ragged or missing trailing spaces.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .collect::<Vec<_>>();
+        let labels = lines.pop().context("The drawing is empty")?;
+
+        // the column span of every stack number
+        let mut spans = vec![];
+        let mut start = None;
+        for (i, c) in labels.char_indices().chain([(labels.len(), ' ')]) {
+            match (c.is_ascii_digit(), start) {
+                (true, None) => start = Some(i),
+                (false, Some(st)) => {
+                    spans.push((st, i));
+                    start = None;
+                }
+                (false, None) if c != ' ' => bail!("Unexpected '{}' in the stack numbers", c),
+                _ => {}
+            }
+        }
+        ensure!(!spans.is_empty(), "The drawing has no stack numbers");
+
+        let mut stacks = vec![vec![]; spans.len()];
+        for line in lines.iter().rev() {
+            for (col, c) in line.char_indices() {
+                if c == ' ' || c == '[' || c == ']' {
+                    continue;
+                }
+                let distance = |(st, end): (usize, usize)| {
+                    col.saturating_sub(end - 1).max(st.saturating_sub(col))
+                };
+                let stack = (0..spans.len())
+                    .min_by_key(|&i| distance(spans[i]))
+                    .unwrap();
+                ensure!(
+                    distance(spans[stack]) <= 1,
+                    "Crate '{}' in '{}' isn't above a stack number",
+                    c,
+                    line
+                );
+                stacks[stack].push(c);
+            }
+        }
+        Ok(Self { stacks })
+    }
+}
+
+impl Display for Stacks {
+    /// Drawn the way the puzzle does
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let height = self.stacks.iter().map(Vec::len).max().unwrap_or(0);
+        for level in (0..height).rev() {
+            let row = self
+                .stacks
+                .iter()
+                .map(|s| {
+                    s.get(level)
+                        .map_or("   ".to_string(), |c| format!("[{}]", c))
+                })
+                .collect::<Vec<_>>();
+            writeln!(f, "{}", row.join(" "))?;
+        }
+        let labels = (1..=self.stacks.len())
+            .map(|i| format!(" {} ", i))
+            .collect::<Vec<_>>();
+        writeln!(f, "{}", labels.join(" "))
+    }
+}
+
+/// Parse the drawing and the moves that follow it
+pub fn parse_procedure(input: &str) -> Result<(Stacks, Vec<Move>)> {
+    let (drawing, moves) = input
+        .split_once("\n\n")
+        .context("The drawing and the moves should be separated by a blank line")?;
+    let moves = moves
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(ln, l)| l.parse().with_context(|| format!("Move {}", ln + 1)))
+        .collect::<Result<_>>()?;
+    Ok((drawing.parse()?, moves))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DRAWING: &str = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n";
+    const SAMPLE: &str = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\nmove 1 from 2 to 1\nmove 3 from 1 to 3\nmove 2 from 2 to 1\nmove 1 from 1 to 2\n";
+
+    #[test]
+    fn drawing_round_trips() {
+        let stacks = DRAWING.parse::<Stacks>().unwrap();
+        assert_eq!(
+            stacks.stacks(),
+            &[vec!['Z', 'N'], vec!['M', 'C', 'D'], vec!['P']]
+        );
+        assert_eq!(stacks.to_string(), DRAWING);
+    }
+
+    #[test]
+    fn ragged_drawings() {
+        // no trailing spaces, and more than 9 stacks
+        let drawing =
+            "[A]\n[B]                                 [C]\n 1   2   3   4   5   6   7   8   9   10";
+        let stacks = drawing.parse::<Stacks>().unwrap();
+        assert_eq!(stacks.stacks().len(), 10);
+        assert_eq!(stacks.stacks()[0], vec!['B', 'A']);
+        assert_eq!(stacks.stacks()[9], vec!['C']);
+    }
+
+    #[test]
+    fn strategies() {
+        let (stacks, moves) = parse_procedure(SAMPLE).unwrap();
+        let mut one = stacks.clone();
+        one.run(&moves, &OneAtATime).unwrap();
+        assert_eq!(one.tops(), "CMZ");
+        let mut batch = stacks;
+        batch.run(&moves, &Batch).unwrap();
+        assert_eq!(batch.tops(), "MCD");
+    }
+
+    #[test]
+    fn prints_each_step() {
+        let (mut stacks, moves) = parse_procedure(SAMPLE).unwrap();
+        let mut frames = vec![];
+        stacks
+            .run_with(&moves, &OneAtATime, |_, s| frames.push(s.to_string()))
+            .unwrap();
+        assert_eq!(frames.len(), 4);
+        assert_eq!(
+            frames[0],
+            "[D]        \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n"
+        );
+    }
+
+    #[test]
+    fn moving_too_many_crates_is_an_error() {
+        let mut stacks = DRAWING.parse::<Stacks>().unwrap();
+        let e = stacks
+            .run(&["move 3 from 1 to 2".parse().unwrap()], &Batch)
+            .unwrap_err();
+        assert_eq!(
+            format!("{:#}", e),
+            "Move 1: Can't move 3 crates from stack 1, it only has 2"
+        );
+        assert!(stacks
+            .apply("move 1 from 4 to 1".parse().unwrap(), &Batch)
+            .is_err());
+        assert!("move 1 from 0 to 1".parse::<Move>().is_err());
+    }
+}
@@ -0,0 +1,335 @@
+//! An in-memory filesystem tree, built by replaying a shell transcript like the one in day 7.
+//!
+//! Nodes live in an arena and refer to each other by [`NodeId`], every node knows its parent so
+//! `cd ..` and full paths are cheap. Children keep the order they were first listed in.
+use anyhow::{bail, Context, Result};
+
+pub type NodeId = usize;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeKind {
+    Dir(Vec<NodeId>),
+    File(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    pub name: String,
+    pub parent: Option<NodeId>,
+    pub kind: NodeKind,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileSystem {
+    nodes: Vec<Node>,
+    cwd: NodeId,
+}
+
+impl Default for FileSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FileSystem {
+    pub const ROOT: NodeId = 0;
+
+    /// An empty filesystem, with the root as the working directory
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![Node {
+                name: "/".to_string(),
+                parent: None,
+                kind: NodeKind::Dir(vec![]),
+            }],
+            cwd: Self::ROOT,
+        }
+    }
+
+    /// Replay a transcript of `$ cd` and `$ ls` commands and their output
+    pub fn from_transcript(input: &str) -> Result<Self> {
+        let mut fs = Self::new();
+        for (ln, line) in input.lines().enumerate() {
+            fs.replay(line)
+                .with_context(|| format!("Line {}: \"{}\"", ln + 1, line))?;
+        }
+        Ok(fs)
+    }
+
+    /// Replay one line of a transcript, either a command or a line of `ls` output
+    pub fn replay(&mut self, line: &str) -> Result<()> {
+        if let Some(command) = line.strip_prefix("$ ") {
+            match command.split_once(' ') {
+                Some(("cd", target)) => self.cd(target.trim()),
+                None if command == "ls" => Ok(()),
+                _ => bail!("Unknown command"),
+            }
+        } else if let Some(name) = line.strip_prefix("dir ") {
+            self.mkdir(name).map(|_| ())
+        } else {
+            let (size, name) = line
+                .split_once(' ')
+                .context("File entry should have bytes and file name")?;
+            let size = size.parse().context("Bytes should be an integer")?;
+            self.add_file(name, size).map(|_| ())
+        }
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id]
+    }
+
+    pub fn cwd(&self) -> NodeId {
+        self.cwd
+    }
+
+    pub fn is_dir(&self, id: NodeId) -> bool {
+        matches!(self.nodes[id].kind, NodeKind::Dir(_))
+    }
+
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        match &self.nodes[id].kind {
+            NodeKind::Dir(children) => children,
+            NodeKind::File(_) => &[],
+        }
+    }
+
+    pub fn child(&self, dir: NodeId, name: &str) -> Option<NodeId> {
+        self.children(dir)
+            .iter()
+            .copied()
+            .find(|&c| self.nodes[c].name == name)
+    }
+
+    /// Change the working directory to `/`, `..` or a directory inside the current one, creating
+    /// it if it hasn't been listed yet
+    pub fn cd(&mut self, target: &str) -> Result<()> {
+        self.cwd = match target {
+            "" => bail!("cd command target should not be blank"),
+            "/" => Self::ROOT,
+            ".." => self.nodes[self.cwd]
+                .parent
+                .context("Can't cd above the root directory")?,
+            name => self.mkdir(name)?,
+        };
+        Ok(())
+    }
+
+    /// Create a directory in the working directory, or return it if it already exists
+    pub fn mkdir(&mut self, name: &str) -> Result<NodeId> {
+        self.insert(name, NodeKind::Dir(vec![]))
+    }
+
+    /// Create a file in the working directory, or return it if an identical one already exists
+    pub fn add_file(&mut self, name: &str, size: usize) -> Result<NodeId> {
+        self.insert(name, NodeKind::File(size))
+    }
+
+    fn insert(&mut self, name: &str, kind: NodeKind) -> Result<NodeId> {
+        if let Some(existing) = self.child(self.cwd, name) {
+            match (&self.nodes[existing].kind, &kind) {
+                (NodeKind::Dir(_), NodeKind::Dir(_)) => return Ok(existing),
+                (NodeKind::File(a), NodeKind::File(b)) if a == b => return Ok(existing),
+                _ => bail!("'{}' already exists as something else", self.path(existing)),
+            }
+        }
+        let id = self.nodes.len();
+        self.nodes.push(Node {
+            name: name.to_string(),
+            parent: Some(self.cwd),
+            kind,
+        });
+        if let NodeKind::Dir(children) = &mut self.nodes[self.cwd].kind {
+            children.push(id);
+        }
+        Ok(id)
+    }
+
+    /// Find a node from an absolute path like `/a/e`
+    pub fn find(&self, path: &str) -> Option<NodeId> {
+        path.split('/')
+            .filter(|p| !p.is_empty())
+            .try_fold(Self::ROOT, |dir, name| self.child(dir, name))
+    }
+
+    /// The absolute path of a node
+    pub fn path(&self, id: NodeId) -> String {
+        let mut names = vec![];
+        let mut node = id;
+        while let Some(parent) = self.nodes[node].parent {
+            names.push(self.nodes[node].name.as_str());
+            node = parent;
+        }
+        names.reverse();
+        format!("/{}", names.join("/"))
+    }
+
+    /// `id` and everything below it, parents before their children
+    pub fn walk(&self, id: NodeId) -> Vec<NodeId> {
+        let mut order = vec![];
+        let mut stack = vec![id];
+        while let Some(node) = stack.pop() {
+            order.push(node);
+            stack.extend(self.children(node).iter().rev());
+        }
+        order
+    }
+
+    /// The directories at or below `id`
+    pub fn dirs(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.walk(id).into_iter().filter(|&n| self.is_dir(n))
+    }
+
+    /// Recursive size of every node, indexed by [`NodeId`]
+    pub fn sizes(&self) -> Vec<usize> {
+        let mut sizes = self
+            .nodes
+            .iter()
+            .map(|n| match n.kind {
+                NodeKind::File(size) => size,
+                NodeKind::Dir(_) => 0,
+            })
+            .collect::<Vec<_>>();
+        // children are always created after their parents
+        for id in (1..self.nodes.len()).rev() {
+            let parent = self.nodes[id].parent.expect("only the root has no parent");
+            sizes[parent] += sizes[id];
+        }
+        sizes
+    }
+
+    /// Recursive size of a single node
+    pub fn size(&self, id: NodeId) -> usize {
+        self.walk(id)
+            .into_iter()
+            .map(|n| match self.nodes[n].kind {
+                NodeKind::File(size) => size,
+                NodeKind::Dir(_) => 0,
+            })
+            .sum()
+    }
+
+    /// Directory sizes and paths at or below `id`, children before their parents like `du`
+    pub fn du(&self, id: NodeId) -> String {
+        let sizes = self.sizes();
+        let mut dirs = self.dirs(id).collect::<Vec<_>>();
+        dirs.reverse();
+        dirs.into_iter()
+            .map(|d| format!("{}\t{}\n", sizes[d], self.path(d)))
+            .collect()
+    }
+
+    /// Draw `id` and everything below it the way the puzzle does
+    pub fn tree(&self, id: NodeId) -> String {
+        let mut out = String::new();
+        let mut stack = vec![(id, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            let Node { name, kind, .. } = &self.nodes[node];
+            let indent = "  ".repeat(depth);
+            match kind {
+                NodeKind::Dir(children) => {
+                    out.push_str(&format!("{}- {} (dir)\n", indent, name));
+                    stack.extend(children.iter().rev().map(|&c| (c, depth + 1)));
+                }
+                NodeKind::File(size) => {
+                    out.push_str(&format!("{}- {} (file, size={})\n", indent, name, size));
+                }
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "\
+$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k
+";
+
+    #[test]
+    fn tree_matches_puzzle() {
+        let fs = FileSystem::from_transcript(SAMPLE).unwrap();
+        let expected = "\
+- / (dir)
+  - a (dir)
+    - e (dir)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir)
+    - j (file, size=4060174)
+    - d.log (file, size=8033020)
+    - d.ext (file, size=5626152)
+    - k (file, size=7214296)
+";
+        assert_eq!(fs.tree(FileSystem::ROOT), expected);
+    }
+
+    #[test]
+    fn sizes_and_du() {
+        let fs = FileSystem::from_transcript(SAMPLE).unwrap();
+        let e = fs.find("/a/e").unwrap();
+        assert_eq!(fs.size(e), 584);
+        assert_eq!(fs.size(fs.find("/a").unwrap()), 94853);
+        assert_eq!(fs.sizes()[FileSystem::ROOT], 48381165);
+        assert_eq!(fs.path(e), "/a/e");
+        assert_eq!(
+            fs.du(FileSystem::ROOT),
+            "24933642\t/d\n584\t/a/e\n94853\t/a\n48381165\t/\n"
+        );
+    }
+
+    #[test]
+    fn traversal_and_parents() {
+        let fs = FileSystem::from_transcript(SAMPLE).unwrap();
+        assert_eq!(fs.cwd(), fs.find("/d").unwrap());
+        let a = fs.find("/a").unwrap();
+        let names = fs
+            .walk(a)
+            .into_iter()
+            .map(|n| fs.node(n).name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["a", "e", "i", "f", "g", "h.lst"]);
+        assert_eq!(fs.node(a).parent, Some(FileSystem::ROOT));
+        assert_eq!(fs.find("/a/x"), None);
+    }
+
+    #[test]
+    fn bad_transcripts_are_errors() {
+        let e = FileSystem::from_transcript("$ cd /\n$ cd ..").unwrap_err();
+        assert_eq!(e.to_string(), "Line 2: \"$ cd ..\"");
+        let e = FileSystem::from_transcript("$ rm -rf /").unwrap_err();
+        assert_eq!(
+            format!("{:#}", e),
+            "Line 1: \"$ rm -rf /\": Unknown command"
+        );
+        assert!(FileSystem::from_transcript("dir a\n12 a").is_err());
+    }
+}
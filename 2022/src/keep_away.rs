@@ -0,0 +1,514 @@
+//! Agents passing items to each other in rounds, like the monkeys playing keep away in day 11.
+//!
+//! [`Simulation`] runs any [`Agent`] and keeps per round statistics, [`Monkey`] is the day 11
+//! agent whose worry level operation can be any arithmetic expression of `old`.
+use anyhow::{bail, ensure, Context, Error, Result};
+use std::fmt::{Display, Write};
+use std::str::FromStr;
+
+pub type Item = i64;
+
+/// An arithmetic expression of the `old` worry level
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Num(Item),
+    Old,
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Div(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// `None` on overflow or division by zero
+    pub fn eval(&self, old: Item) -> Option<Item> {
+        match self {
+            Expr::Num(n) => Some(*n),
+            Expr::Old => Some(old),
+            Expr::Add(a, b) => a.eval(old)?.checked_add(b.eval(old)?),
+            Expr::Sub(a, b) => a.eval(old)?.checked_sub(b.eval(old)?),
+            Expr::Mul(a, b) => a.eval(old)?.checked_mul(b.eval(old)?),
+            Expr::Div(a, b) => a.eval(old)?.checked_div(b.eval(old)?),
+        }
+    }
+
+    /// Whether `eval(old % m) % m == eval(old) % m`, i.e. whether worry levels can be kept
+    /// modulo the test divisors without changing where items are thrown
+    pub fn is_modular(&self) -> bool {
+        match self {
+            Expr::Num(_) | Expr::Old => true,
+            Expr::Add(a, b) | Expr::Sub(a, b) | Expr::Mul(a, b) => a.is_modular() && b.is_modular(),
+            Expr::Div(..) => false,
+        }
+    }
+}
+
+/// Recursive descent over `+ - * /`, parentheses, integers and `old`
+struct ExprParser<'a> {
+    tokens: Vec<&'a str>,
+    pos: usize,
+}
+
+impl<'a> ExprParser<'a> {
+    fn new(s: &'a str) -> Self {
+        let mut tokens = vec![];
+        let mut start = None;
+        for (i, c) in s.char_indices() {
+            if c.is_ascii_alphanumeric() {
+                start.get_or_insert(i);
+                continue;
+            }
+            if let Some(st) = start.take() {
+                tokens.push(&s[st..i]);
+            }
+            if !c.is_whitespace() {
+                tokens.push(&s[i..i + c.len_utf8()]);
+            }
+        }
+        if let Some(st) = start {
+            tokens.push(&s[st..]);
+        }
+        Self { tokens, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<&'a str> {
+        let t = self.peek();
+        self.pos += 1;
+        t
+    }
+
+    fn expr(&mut self) -> Result<Expr> {
+        let mut lhs = self.term()?;
+        while let Some(op @ ("+" | "-")) = self.peek() {
+            self.next();
+            let rhs = Box::new(self.term()?);
+            lhs = match op {
+                "+" => Expr::Add(Box::new(lhs), rhs),
+                _ => Expr::Sub(Box::new(lhs), rhs),
+            };
+        }
+        Ok(lhs)
+    }
+
+    fn term(&mut self) -> Result<Expr> {
+        let mut lhs = self.factor()?;
+        while let Some(op @ ("*" | "/")) = self.peek() {
+            self.next();
+            let rhs = Box::new(self.factor()?);
+            lhs = match op {
+                "*" => Expr::Mul(Box::new(lhs), rhs),
+                _ => Expr::Div(Box::new(lhs), rhs),
+            };
+        }
+        Ok(lhs)
+    }
+
+    fn factor(&mut self) -> Result<Expr> {
+        match self.next() {
+            Some("old") => Ok(Expr::Old),
+            Some("(") => {
+                let e = self.expr()?;
+                ensure!(self.next() == Some(")"), "Expected ')'");
+                Ok(e)
+            }
+            Some(n) if n.chars().all(|c| c.is_ascii_digit()) => Ok(Expr::Num(n.parse()?)),
+            Some(t) => bail!("Unexpected '{}'", t),
+            None => bail!("Unexpected end of expression"),
+        }
+    }
+}
+
+impl FromStr for Expr {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = ExprParser::new(s);
+        let e = parser
+            .expr()
+            .with_context(|| format!("Could not parse expression: '{}'", s))?;
+        if let Some(t) = parser.peek() {
+            bail!("Unexpected '{}' in expression: '{}'", t, s);
+        }
+        Ok(e)
+    }
+}
+
+pub trait Agent {
+    type Item: Clone;
+
+    /// Inspect an item, returning its new value and the agent it's thrown to
+    fn inspect(&self, item: Self::Item) -> Result<(Self::Item, usize)>;
+}
+
+/// One inspection, as recorded in an agent's log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inspection<T> {
+    pub round: usize,
+    pub before: T,
+    pub after: T,
+    pub target: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundStats {
+    pub round: usize,
+    /// Items each agent inspected during the round
+    pub inspections: Vec<usize>,
+    /// Items each agent holds once the round is over
+    pub held: Vec<usize>,
+}
+
+/// Every agent takes a turn each round, inspecting and throwing all of its items in order
+pub struct Simulation<A: Agent> {
+    /// What the agents are called when printed
+    label: String,
+    agents: Vec<A>,
+    items: Vec<Vec<A::Item>>,
+    inspections: Vec<usize>,
+    round: usize,
+    stats: Vec<RoundStats>,
+    log: Option<Vec<Vec<Inspection<A::Item>>>>,
+}
+
+impl<A: Agent> Simulation<A> {
+    /// Agents along with the items they start out holding
+    pub fn new(agents: Vec<(A, Vec<A::Item>)>) -> Self {
+        let (agents, items): (Vec<_>, Vec<_>) = agents.into_iter().unzip();
+        Self {
+            label: "Agent".to_string(),
+            inspections: vec![0; agents.len()],
+            agents,
+            items,
+            round: 0,
+            stats: vec![],
+            log: None,
+        }
+    }
+
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.label = label.to_string();
+        self
+    }
+
+    /// Start recording every inspection from now on
+    pub fn enable_log(&mut self) {
+        let n = self.agents.len();
+        self.log.get_or_insert_with(|| vec![vec![]; n]);
+    }
+
+    /// Inspections `agent` made since logging was enabled
+    pub fn log(&self, agent: usize) -> &[Inspection<A::Item>] {
+        self.log.as_ref().map_or(&[], |log| &log[agent])
+    }
+
+    pub fn agents(&self) -> &[A] {
+        &self.agents
+    }
+
+    pub fn items(&self, agent: usize) -> &[A::Item] {
+        &self.items[agent]
+    }
+
+    /// Total inspections per agent
+    pub fn inspections(&self) -> &[usize] {
+        &self.inspections
+    }
+
+    pub fn stats(&self) -> &[RoundStats] {
+        &self.stats
+    }
+
+    /// Run a round, stopping at the first inspection that fails
+    pub fn round(&mut self) -> Result<()> {
+        self.round += 1;
+        let mut inspections = vec![0; self.agents.len()];
+        for idx in 0..self.agents.len() {
+            let items = std::mem::take(&mut self.items[idx]);
+            inspections[idx] = items.len();
+            for item in items {
+                let (after, target) = self.agents[idx]
+                    .inspect(item.clone())
+                    .with_context(|| format!("{} {} in round {}", self.label, idx, self.round))?;
+                if let Some(log) = self.log.as_mut() {
+                    log[idx].push(Inspection {
+                        round: self.round,
+                        before: item,
+                        after: after.clone(),
+                        target,
+                    });
+                }
+                self.items[target].push(after);
+            }
+        }
+        for (total, n) in self.inspections.iter_mut().zip(&inspections) {
+            *total += n;
+        }
+        self.stats.push(RoundStats {
+            round: self.round,
+            inspections,
+            held: self.items.iter().map(Vec::len).collect(),
+        });
+        Ok(())
+    }
+
+    pub fn rounds(&mut self, n: usize) -> Result<()> {
+        for _ in 0..n {
+            self.round()?;
+        }
+        Ok(())
+    }
+
+    /// Product of the `n` largest inspection counts
+    pub fn business(&self, n: usize) -> usize {
+        let mut counts = self.inspections.clone();
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+        counts.iter().take(n).product()
+    }
+
+    /// Round statistics as CSV, one row per agent per round, ready for plotting
+    pub fn stats_csv(&self) -> String {
+        let mut csv = String::from("round,agent,inspections,total_inspections,held\n");
+        let mut totals = vec![0; self.agents.len()];
+        for stats in &self.stats {
+            for (agent, (&n, &held)) in stats.inspections.iter().zip(&stats.held).enumerate() {
+                totals[agent] += n;
+                writeln!(
+                    csv,
+                    "{},{},{},{},{}",
+                    stats.round, agent, n, totals[agent], held
+                )
+                .unwrap();
+            }
+        }
+        csv
+    }
+}
+
+impl<A> Display for Simulation<A>
+where
+    A: Agent,
+    A::Item: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, items) in self.items.iter().enumerate() {
+            let xs = items.iter().map(ToString::to_string).collect::<Vec<_>>();
+            writeln!(f, "{} {} has Items: {}", self.label, i, xs.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Monkey {
+    pub op: Expr,
+    pub test: Item,
+    pub positive_target: usize,
+    pub negative_target: usize,
+    /// Worry levels are divided by this after every inspection
+    pub relief: Item,
+    /// Worry levels are kept modulo this, if set
+    pub modulus: Option<Item>,
+}
+
+impl Agent for Monkey {
+    type Item = Item;
+
+    fn inspect(&self, item: Item) -> Result<(Item, usize)> {
+        let worry = self
+            .op
+            .eval(item)
+            .with_context(|| format!("Worry level {} overflowed or was divided by zero", item))?;
+        let mut item = worry
+            .checked_div(self.relief)
+            .context("Relief can't be zero")?;
+        if let Some(m) = self.modulus {
+            item = item
+                .checked_rem_euclid(m)
+                .context("Modulus can't be zero")?;
+        }
+        let divisible = item.checked_rem(self.test).context("Test can't be zero")? == 0;
+        let target = if divisible {
+            self.positive_target
+        } else {
+            self.negative_target
+        };
+        Ok((item, target))
+    }
+}
+
+/// Parse a `Monkey N:` block into the monkey and its starting items
+pub fn parse_monkey(s: &str) -> Result<(Monkey, Vec<Item>)> {
+    let context = || format!(r#"Failed to parse monkey from: "\n{}\n""#, s);
+    let field = |name: &str| {
+        s.lines()
+            .find_map(|l| l.trim().strip_prefix(name))
+            .map(str::trim)
+            .with_context(|| format!("Missing '{}'", name))
+            .with_context(context)
+    };
+    let last_number = |name: &str| -> Result<Item> {
+        let value = field(name)?;
+        value
+            .rsplit(' ')
+            .next()
+            .unwrap_or_default()
+            .parse()
+            .with_context(|| format!("Expected a number in '{} {}'", name, value))
+    };
+
+    let items = field("Starting items:")?
+        .split(',')
+        .map(|item| Ok(item.trim().parse::<Item>()?))
+        .collect::<Result<_>>()
+        .with_context(context)?;
+    let op = field("Operation:")?
+        .strip_prefix("new =")
+        .context("Operation should assign to new")
+        .with_context(context)?
+        .parse()
+        .with_context(context)?;
+
+    let test = last_number("Test:")?;
+    ensure!(test != 0, "Monkeys can't test for divisibility by 0");
+    let monkey = Monkey {
+        op,
+        test,
+        positive_target: last_number("If true:")? as usize,
+        negative_target: last_number("If false:")? as usize,
+        relief: 1,
+        modulus: None,
+    };
+    Ok((monkey, items))
+}
+
+fn gcd(a: Item, b: Item) -> Item {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Set up a game of keep away. Without relief worry levels grow without bound, so if every
+/// monkey's operation allows it they are kept modulo the least common multiple of the tests.
+pub fn keep_away(input: &str, relief: bool) -> Result<Simulation<Monkey>> {
+    let mut monkeys = input
+        .split("\n\n")
+        .map(parse_monkey)
+        .collect::<Result<Vec<_>>>()?;
+    for (i, (m, _)) in monkeys.iter().enumerate() {
+        ensure!(
+            m.positive_target < monkeys.len() && m.negative_target < monkeys.len(),
+            "Monkey {} throws to a monkey that doesn't exist",
+            i
+        );
+    }
+
+    let modulus = monkeys.iter().all(|(m, _)| m.op.is_modular()).then(|| {
+        monkeys
+            .iter()
+            .fold(1, |acc, (m, _)| acc / gcd(acc, m.test) * m.test)
+    });
+    for (m, _) in monkeys.iter_mut() {
+        if relief {
+            m.relief = 3;
+        } else {
+            m.modulus = modulus;
+        }
+    }
+    Ok(Simulation::new(monkeys).with_label("Monkey"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = include_str!("../inputs/day11/sample.txt");
+
+    #[test]
+    fn expressions() {
+        let e: Expr = "(old + 3) * 2 - old / 4".parse().unwrap();
+        assert_eq!(e.eval(8), Some(20));
+        assert!(!e.is_modular());
+        let e: Expr = "old * old".parse().unwrap();
+        assert_eq!(e, Expr::Mul(Box::new(Expr::Old), Box::new(Expr::Old)));
+        assert!(e.is_modular());
+        assert!("old +".parse::<Expr>().is_err());
+        assert!("old 3".parse::<Expr>().is_err());
+        assert!("new * 3".parse::<Expr>().is_err());
+        let e: Expr = "old / (old - 3)".parse().unwrap();
+        assert_eq!(e.eval(3), None);
+        assert_eq!(e.eval(5), Some(2));
+    }
+
+    #[test]
+    fn dividing_by_zero_is_an_error() {
+        assert!(keep_away(&SAMPLE.replace("by 23", "by 0"), true).is_err());
+        let mut game = keep_away(&SAMPLE.replace("old * 19", "old / (old - 79)"), true).unwrap();
+        let e = game.round().unwrap_err();
+        assert_eq!(e.to_string(), "Monkey 0 in round 1");
+    }
+
+    #[test]
+    fn first_round_matches_puzzle() {
+        let mut game = keep_away(SAMPLE, true).unwrap();
+        game.round().unwrap();
+        assert_eq!(game.items(0), &[20, 23, 27, 26]);
+        assert_eq!(game.items(1), &[2080, 25, 167, 207, 401, 1046]);
+        assert!(game.items(2).is_empty());
+        assert_eq!(
+            game.to_string(),
+            "Monkey 0 has Items: 20, 23, 27, 26\n\
+             Monkey 1 has Items: 2080, 25, 167, 207, 401, 1046\n\
+             Monkey 2 has Items: \n\
+             Monkey 3 has Items: \n"
+        );
+    }
+
+    #[test]
+    fn inspection_log() {
+        let mut game = keep_away(SAMPLE, true).unwrap();
+        game.enable_log();
+        game.round().unwrap();
+        assert_eq!(
+            game.log(0),
+            &[
+                Inspection {
+                    round: 1,
+                    before: 79,
+                    after: 500,
+                    target: 3
+                },
+                Inspection {
+                    round: 1,
+                    before: 98,
+                    after: 620,
+                    target: 3
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn modulus_is_lcm_of_tests() {
+        let game = keep_away(SAMPLE, false).unwrap();
+        assert_eq!(game.agents()[0].modulus, Some(23 * 19 * 13 * 17));
+        let game = keep_away(&SAMPLE.replace("old + 3", "old / 2 + 3"), false).unwrap();
+        assert_eq!(game.agents()[0].modulus, None);
+    }
+
+    #[test]
+    fn round_stats() {
+        let mut game = keep_away(SAMPLE, false).unwrap();
+        game.rounds(20).unwrap();
+        assert_eq!(game.stats().len(), 20);
+        assert_eq!(game.stats()[0].inspections, vec![2, 4, 3, 6]);
+        assert_eq!(game.inspections(), &[99, 97, 8, 103]);
+        let csv = game.stats_csv();
+        assert!(csv.starts_with("round,agent,inspections,total_inspections,held\n1,0,2,2,4\n"));
+        assert!(csv.ends_with("20,3,5,103,0\n"));
+    }
+}
@@ -1,3 +1,14 @@
+pub mod crates;
+pub mod filesystem;
+pub mod keep_away;
+pub mod ocr;
+pub mod parse_error;
+pub mod rope;
+pub mod sand;
+pub mod sensors;
+pub mod vector;
+pub mod vm;
+
 use std::collections::HashSet;
 use std::fs;
 use std::io::read_to_string;
@@ -0,0 +1,189 @@
+//! Read the block letters some puzzles draw instead of printing an answer.
+//!
+//! Knows the 4x6 font (CRT style screens) and the 6x10 font (moving stars style messages).
+//! Letters are found by splitting the drawing on fully dark columns, so the exact letter spacing
+//! doesn't matter.
+use crate::vector::Vec2;
+use anyhow::{bail, Result};
+use std::collections::HashSet;
+
+const SMALL_FONT: &[(char, &str)] = &[
+    ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+    ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+    ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+    ('E', "####\n#...\n###.\n#...\n#...\n####"),
+    ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+    ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+    ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+    ('I', "###\n.#.\n.#.\n.#.\n.#.\n###"),
+    ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+    ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+    ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+    ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+    ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+    ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+    ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+    ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+];
+
+#[rustfmt::skip]
+const LARGE_FONT: &[(char, &str)] = &[
+    ('A', "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#"),
+    ('B', "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####."),
+    ('C', ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####."),
+    ('E', "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######"),
+    ('F', "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#....."),
+    ('G', ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#"),
+    ('H', "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#"),
+    ('J', "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n.###.."),
+    ('K', "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#"),
+    ('L', "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######"),
+    ('N', "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#"),
+    ('P', "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#....."),
+    ('R', "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#"),
+    ('X', "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#"),
+    ('Z', "######\n.....#\n.....#\n....#.\n...#..\n..#...\n.#....\n#.....\n#.....\n######"),
+];
+
+/// Read the text in a drawing where `#` is a lit pixel and anything else is dark
+pub fn recognize(drawing: &str) -> Result<String> {
+    let mut rows = drawing
+        .lines()
+        .map(|l| l.chars().map(|c| c == '#').collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    for row in rows.iter_mut() {
+        row.resize(width, false);
+    }
+    rows.retain(|row| row.contains(&true));
+
+    let font = match rows.len() {
+        6 => SMALL_FONT,
+        10 => LARGE_FONT,
+        0 => bail!("Nothing is drawn"),
+        h => bail!("No font is {} pixels tall", h),
+    };
+
+    let lit_column = |x: usize| rows.iter().any(|row| row[x]);
+    let mut text = String::new();
+    let mut x = 0;
+    while x < width {
+        if !lit_column(x) {
+            x += 1;
+            continue;
+        }
+        let start = x;
+        while x < width && lit_column(x) {
+            x += 1;
+        }
+        let glyph = rows
+            .iter()
+            .map(|row| {
+                row[start..x]
+                    .iter()
+                    .map(|&lit| if lit { '#' } else { '.' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        match font.iter().find(|(_, g)| *g == glyph) {
+            Some((c, _)) => text.push(*c),
+            None => bail!("Unknown letter at column {}:\n{}", start, glyph),
+        }
+    }
+    Ok(text)
+}
+
+/// Read the text drawn by a set of lit points, wherever they are
+pub fn recognize_points(points: impl IntoIterator<Item = Vec2>) -> Result<String> {
+    let points = points.into_iter().collect::<HashSet<_>>();
+    let (Some(min_x), Some(max_x)) = (
+        points.iter().map(|p| p.x).min(),
+        points.iter().map(|p| p.x).max(),
+    ) else {
+        bail!("Nothing is drawn")
+    };
+    let min_y = points.iter().map(|p| p.y).min().unwrap();
+    let max_y = points.iter().map(|p| p.y).max().unwrap();
+
+    let drawing = (min_y..=max_y)
+        .map(|y| {
+            (min_x..=max_x)
+                .map(|x| {
+                    if points.contains(&Vec2::new(x, y)) {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    recognize(&drawing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crt_screen() {
+        let screen = "\
+###...##..#..#.####..##..#....#..#..##..
+#..#.#..#.#..#.#....#..#.#....#..#.#..#.
+#..#.#....####.###..#....#....#..#.#....
+###..#.##.#..#.#....#.##.#....#..#.#.##.
+#....#..#.#..#.#....#..#.#....#..#.#..#.
+#.....###.#..#.#.....###.####..##...###.
+";
+        assert_eq!(recognize(screen).unwrap(), "PGHFGLUG");
+    }
+
+    #[test]
+    fn every_small_letter() {
+        let rows = (0..6)
+            .map(|y| {
+                SMALL_FONT
+                    .iter()
+                    .map(|(_, g)| g.lines().nth(y).unwrap())
+                    .collect::<Vec<_>>()
+                    .join("..")
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(recognize(&rows).unwrap(), "ABCEFGHIJKLOPRSUYZ");
+    }
+
+    #[test]
+    fn large_font_from_points() {
+        // spell "NX" from points at an arbitrary offset
+        let points = ["N", "X"]
+            .iter()
+            .enumerate()
+            .flat_map(|(i, letter)| {
+                let glyph = LARGE_FONT.iter().find(|(c, _)| c.to_string() == *letter);
+                glyph
+                    .unwrap()
+                    .1
+                    .lines()
+                    .enumerate()
+                    .flat_map(move |(y, row)| {
+                        row.char_indices()
+                            .filter(|(_, c)| *c == '#')
+                            .map(move |(x, _)| Vec2::new(x as isize + 8 * i as isize, y as isize))
+                    })
+            })
+            .map(|p| p + Vec2::new(100, -7))
+            .collect::<Vec<_>>();
+        assert_eq!(recognize_points(points).unwrap(), "NX");
+    }
+
+    #[test]
+    fn unknown_letters_are_errors() {
+        let e = recognize("#.#\n.#.\n#.#\n.#.\n#.#\n.#.").unwrap_err();
+        assert!(e.to_string().starts_with("Unknown letter at column 0"));
+    }
+}
@@ -0,0 +1,215 @@
+//! A rope of knots dragged around by its head, like the rope bridge in day 9.
+//!
+//! Every knot follows the one in front of it, moving one step (diagonally if needed) whenever
+//! they stop touching. The positions every knot has visited are kept so any of them can be asked
+//! about, not just the tail.
+use crate::vector::Vec2;
+use anyhow::{bail, Context, Result};
+use std::collections::HashSet;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub fn vector(&self) -> Vec2 {
+        match self {
+            Direction::Up => Vec2::UP,
+            Direction::Down => Vec2::DOWN,
+            Direction::Left => Vec2::LEFT,
+            Direction::Right => Vec2::RIGHT,
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "U" => Direction::Up,
+            "D" => Direction::Down,
+            "L" => Direction::Left,
+            "R" => Direction::Right,
+            _ => bail!("Unknown direction '{}'", s),
+        })
+    }
+}
+
+/// Parse `R 4` style motions, one per line
+pub fn parse_motions(input: &str) -> Result<Vec<(Direction, usize)>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(ln, l)| {
+            let (d, n) = l.split_once(' ').with_context(|| {
+                format!("Line {}: '{}' should be a direction and a count", ln + 1, l)
+            })?;
+            Ok((
+                d.parse().with_context(|| format!("Line {}", ln + 1))?,
+                n.parse()
+                    .with_context(|| format!("Line {}: '{}'", ln + 1, n))?,
+            ))
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct Rope {
+    /// The head first, the tail last
+    knots: Vec<Vec2>,
+    visited: Vec<HashSet<Vec2>>,
+}
+
+impl Rope {
+    /// A rope with every knot on the origin, panics if `knots` is 0
+    pub fn new(knots: usize) -> Self {
+        assert!(knots > 0, "A rope needs at least one knot");
+        Self {
+            knots: vec![Vec2::ZERO; knots],
+            visited: vec![HashSet::from([Vec2::ZERO]); knots],
+        }
+    }
+
+    pub fn knots(&self) -> &[Vec2] {
+        &self.knots
+    }
+
+    pub fn head(&self) -> Vec2 {
+        self.knots[0]
+    }
+
+    pub fn tail(&self) -> Vec2 {
+        *self.knots.last().unwrap()
+    }
+
+    /// Every position `knot` has been in, the head is knot 0
+    pub fn visited(&self, knot: usize) -> &HashSet<Vec2> {
+        &self.visited[knot]
+    }
+
+    pub fn tail_visited(&self) -> &HashSet<Vec2> {
+        self.visited.last().unwrap()
+    }
+
+    /// Move the head one step and let the rest of the rope catch up
+    pub fn step(&mut self, direction: Direction) {
+        self.knots[0] += direction.vector();
+        self.visited[0].insert(self.knots[0]);
+        for i in 1..self.knots.len() {
+            let (leader, knot) = (self.knots[i - 1], self.knots[i]);
+            if leader.chebyshev(knot) <= 1 {
+                // the rest of the rope didn't move either
+                break;
+            }
+            self.knots[i] += (leader - knot).signum();
+            self.visited[i].insert(self.knots[i]);
+        }
+    }
+
+    pub fn apply(&mut self, (direction, steps): (Direction, usize)) {
+        for _ in 0..steps {
+            self.step(direction);
+        }
+    }
+
+    pub fn simulate(&mut self, motions: &[(Direction, usize)]) {
+        for &motion in motions {
+            self.apply(motion);
+        }
+    }
+
+    /// Draw the rope like the puzzle's diagrams, `H` for the head, numbers for the other knots
+    /// (`T` when there's only a tail) and `s` for the start. Rows go from `max.y` down to `min.y`.
+    pub fn render(&self, min: Vec2, max: Vec2) -> String {
+        self.draw(min, max, |p| {
+            let knot = self.knots.iter().position(|k| *k == p)?;
+            Some(match knot {
+                0 => 'H',
+                _ if self.knots.len() == 2 => 'T',
+                i => char::from_digit(i as u32, 36).unwrap_or('?'),
+            })
+        })
+    }
+
+    /// Draw the positions `knot` has visited as `#`, with `s` for the start
+    pub fn render_visited(&self, knot: usize, min: Vec2, max: Vec2) -> String {
+        self.draw(min, max, |p| {
+            (p != Vec2::ZERO && self.visited[knot].contains(&p)).then_some('#')
+        })
+    }
+
+    fn draw(&self, min: Vec2, max: Vec2, cell: impl Fn(Vec2) -> Option<char>) -> String {
+        let mut s = String::new();
+        for y in (min.y..=max.y).rev() {
+            for x in min.x..=max.x {
+                let p = Vec2::new(x, y);
+                s.push(cell(p).unwrap_or(if p == Vec2::ZERO { 's' } else { '.' }));
+            }
+            s.push('\n');
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = include_str!("../inputs/day09/sample.txt");
+    const SAMPLE_2: &str = include_str!("../inputs/day09/sample-2.txt");
+
+    fn simulate(input: &str, knots: usize) -> Rope {
+        let mut rope = Rope::new(knots);
+        rope.simulate(&parse_motions(input).unwrap());
+        rope
+    }
+
+    #[test]
+    fn tail_visits() {
+        assert_eq!(simulate(SAMPLE, 2).tail_visited().len(), 13);
+        assert_eq!(simulate(SAMPLE, 10).tail_visited().len(), 1);
+        assert_eq!(simulate(SAMPLE_2, 10).tail_visited().len(), 36);
+    }
+
+    #[test]
+    fn any_knot_can_be_tracked() {
+        // the knot behind the head moves the same no matter how long the rope is
+        let rope = simulate(SAMPLE, 10);
+        assert_eq!(rope.visited(1), simulate(SAMPLE, 2).tail_visited());
+        assert_eq!(rope.visited(1).len(), 13);
+    }
+
+    #[test]
+    fn renders_like_the_puzzle() {
+        let (min, max) = (Vec2::ZERO, Vec2::new(5, 4));
+        let mut rope = Rope::new(10);
+        rope.apply((Direction::Right, 4));
+        assert_eq!(
+            rope.render(min, max),
+            "......\n......\n......\n......\n4321H.\n"
+        );
+        rope.apply((Direction::Up, 4));
+        assert_eq!(
+            rope.render(min, max),
+            "....H.\n....1.\n..432.\n.5....\n6.....\n"
+        );
+
+        let rope = simulate(SAMPLE, 2);
+        assert_eq!(
+            rope.render_visited(1, min, max),
+            "..##..\n...##.\n.####.\n....#.\ns###..\n"
+        );
+    }
+
+    #[test]
+    fn bad_motions_are_errors() {
+        let e = parse_motions("R 4\nX 2").unwrap_err();
+        assert_eq!(e.to_string(), "Line 2");
+        assert!(parse_motions("R four").is_err());
+    }
+}
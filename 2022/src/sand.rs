@@ -0,0 +1,289 @@
+//! Grains falling from a source and piling up on rock, like the sand in day 14.
+//!
+//! A [`Cave`] holds the rock and the grains that came to rest. Each grain tries the movement
+//! rules in order and rests once none of them are free, falling into the abyss below the lowest
+//! rock unless the cave has a floor.
+use crate::vector::Vec2;
+use anyhow::{bail, ensure, Context, Result};
+use std::collections::HashSet;
+use std::fmt::Display;
+
+/// Down, then down-left, then down-right. The cave's y grows downwards.
+pub const SAND: &[Vec2] = &[Vec2::new(0, 1), Vec2::new(-1, 1), Vec2::new(1, 1)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bottom {
+    /// Grains falling below the lowest rock are lost
+    Abyss,
+    /// An infinite floor two below the lowest rock
+    Floor,
+}
+
+/// Parse `x,y -> x,y -> ...` rock paths into every point they cover
+pub fn parse_rock(input: &str) -> Result<HashSet<Vec2>> {
+    let mut rock = HashSet::new();
+    for (ln, line) in input.lines().enumerate() {
+        let points = line
+            .split(" -> ")
+            .map(|p| {
+                let (x, y) = p.split_once(',').context("Point should have a comma")?;
+                Ok(Vec2::new(x.trim().parse()?, y.trim().parse()?))
+            })
+            .collect::<Result<Vec<Vec2>>>()
+            .with_context(|| format!("Line {}: '{}'", ln + 1, line))?;
+        for pair in points.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            ensure!(
+                a.x == b.x || a.y == b.y,
+                "Line {}: {} -> {} is not horizontal or vertical",
+                ln + 1,
+                pair[0],
+                pair[1]
+            );
+            for x in a.x.min(b.x)..=a.x.max(b.x) {
+                for y in a.y.min(b.y)..=a.y.max(b.y) {
+                    rock.insert(Vec2::new(x, y));
+                }
+            }
+        }
+        if let [p] = points[..] {
+            rock.insert(p);
+        }
+    }
+    Ok(rock)
+}
+
+#[derive(Debug, Clone)]
+pub struct Cave {
+    rock: HashSet<Vec2>,
+    sand: HashSet<Vec2>,
+    source: Vec2,
+    rules: Vec<Vec2>,
+    bottom: Bottom,
+    lowest: isize,
+    resume: bool,
+    /// Where the last grain fell through, ending just above where it came to rest
+    path: Vec<Vec2>,
+}
+
+impl Cave {
+    pub fn new(rock: HashSet<Vec2>, source: Vec2) -> Self {
+        let lowest = rock.iter().map(|p| p.y).max().unwrap_or(source.y);
+        Self {
+            rock,
+            sand: HashSet::new(),
+            source,
+            rules: SAND.to_vec(),
+            bottom: Bottom::Abyss,
+            lowest,
+            resume: false,
+            path: vec![],
+        }
+    }
+
+    /// Every rule has to move the grain down, otherwise it could move back and forth forever
+    pub fn with_rules(mut self, rules: &[Vec2]) -> Result<Self> {
+        if let Some(r) = rules.iter().find(|r| r.y <= 0) {
+            bail!("Rule {} doesn't move grains down", r);
+        }
+        self.rules = rules.to_vec();
+        Ok(self)
+    }
+
+    pub fn with_bottom(mut self, bottom: Bottom) -> Self {
+        self.bottom = bottom;
+        self
+    }
+
+    /// Start each grain where the previous one was last free to move instead of at the source.
+    /// Gives the same result, since every grain follows the previous one until it is blocked.
+    pub fn resume_from_path(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    pub fn sand(&self) -> &HashSet<Vec2> {
+        &self.sand
+    }
+
+    pub fn is_blocked(&self, p: Vec2) -> bool {
+        self.rock.contains(&p)
+            || self.sand.contains(&p)
+            || (self.bottom == Bottom::Floor && p.y >= self.lowest + 2)
+    }
+
+    /// Drop one grain, returning where it came to rest, or `None` if it fell into the abyss or
+    /// the source is blocked
+    pub fn drop_grain(&mut self) -> Option<Vec2> {
+        if !self.resume {
+            self.path.clear();
+        }
+        if self.path.is_empty() {
+            if self.is_blocked(self.source) {
+                return None;
+            }
+            self.path.push(self.source);
+        }
+        loop {
+            let p = *self.path.last().expect("path is not empty");
+            if self.bottom == Bottom::Abyss && p.y > self.lowest {
+                return None;
+            }
+            let next = self
+                .rules
+                .iter()
+                .map(|&d| p + d)
+                .find(|&next| !self.is_blocked(next));
+            match next {
+                Some(next) => self.path.push(next),
+                None => {
+                    self.path.pop();
+                    self.sand.insert(p);
+                    return Some(p);
+                }
+            }
+        }
+    }
+
+    /// Drop grains until one doesn't come to rest, returning how many did
+    pub fn fill(&mut self) -> usize {
+        self.fill_with(|_, _| {})
+    }
+
+    /// [`Cave::fill`], handing `hook` the cave after every grain along with where it rested
+    pub fn fill_with(&mut self, mut hook: impl FnMut(&Cave, Vec2)) -> usize {
+        let mut count = 0;
+        while let Some(p) = self.drop_grain() {
+            count += 1;
+            hook(self, p);
+        }
+        count
+    }
+
+    /// Render the cave as frames of the puzzle's text, one every `every` grains
+    pub fn frames(&mut self, every: usize) -> Result<Vec<String>> {
+        if every == 0 {
+            bail!("Frames must be at least one grain apart");
+        }
+        let mut frames = vec![self.to_string()];
+        let count = self.fill_with(|cave, _| {
+            if cave.sand.len().is_multiple_of(every) {
+                frames.push(cave.to_string());
+            }
+        });
+        if !count.is_multiple_of(every) {
+            frames.push(self.to_string());
+        }
+        Ok(frames)
+    }
+}
+
+impl Display for Cave {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let points = || {
+            self.rock
+                .iter()
+                .chain(&self.sand)
+                .chain(std::iter::once(&self.source))
+        };
+        let min_x = points().map(|p| p.x).min().unwrap();
+        let max_x = points().map(|p| p.x).max().unwrap();
+        let min_y = points().map(|p| p.y).min().unwrap();
+        let max_y = match self.bottom {
+            Bottom::Abyss => points().map(|p| p.y).max().unwrap(),
+            Bottom::Floor => self.lowest + 2,
+        };
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let p = Vec2::new(x, y);
+                let c = if self.sand.contains(&p) {
+                    'o'
+                } else if p == self.source {
+                    '+'
+                } else if self.is_blocked(p) {
+                    '#'
+                } else {
+                    '.'
+                };
+                write!(f, "{}", c)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = include_str!("../inputs/day14/sample.txt");
+
+    fn cave() -> Cave {
+        Cave::new(parse_rock(SAMPLE).unwrap(), Vec2::new(500, 0))
+    }
+
+    #[test]
+    fn renders_the_puzzle_picture() {
+        let mut cave = cave();
+        for _ in 0..5 {
+            cave.drop_grain();
+        }
+        let expected = "\
+......+...
+..........
+..........
+..........
+....#...##
+....#...#.
+..###...#.
+......o.#.
+....oooo#.
+#########.
+";
+        assert_eq!(cave.to_string(), expected);
+    }
+
+    #[test]
+    fn abyss_and_floor() {
+        assert_eq!(cave().fill(), 24);
+        assert_eq!(cave().with_bottom(Bottom::Floor).fill(), 93);
+    }
+
+    #[test]
+    fn resuming_matches_dropping_from_the_source() {
+        for bottom in [Bottom::Abyss, Bottom::Floor] {
+            let mut from_source = cave().with_bottom(bottom);
+            let mut resumed = cave().with_bottom(bottom).resume_from_path(true);
+            assert_eq!(from_source.fill(), resumed.fill());
+            assert_eq!(from_source.sand(), resumed.sand());
+        }
+    }
+
+    #[test]
+    fn custom_rules() {
+        // straight down piles up from the rock at y = 9 to the source
+        assert_eq!(cave().with_rules(&[Vec2::new(0, 1)]).unwrap().fill(), 9);
+        let e = cave()
+            .with_rules(&[Vec2::new(0, 1), Vec2::new(1, 0)])
+            .unwrap_err();
+        assert_eq!(e.to_string(), "Rule (1, 0) doesn't move grains down");
+    }
+
+    #[test]
+    fn frames() {
+        let frames = cave().frames(10).unwrap();
+        assert_eq!(frames.len(), 4);
+        assert!(!frames[0].contains('o'));
+        assert_eq!(frames[3].matches('o').count(), 24);
+    }
+
+    #[test]
+    fn diagonal_rock_is_an_error() {
+        let e = parse_rock("1,1 -> 2,2").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "Line 1: (1, 1) -> (2, 2) is not horizontal or vertical"
+        );
+    }
+}
@@ -0,0 +1,236 @@
+//! Sensors that each cover a Manhattan diamond around them, like the beacon exclusion zone in day
+//! 15.
+//!
+//! Rows are never walked cell by cell. A sensor's diamond crosses a row as a single span, and
+//! [`Sensors::coverage`] merges those spans into sorted, disjoint intervals. The one cell left
+//! uncovered in a square has to sit just outside the edges of several diamonds, so
+//! [`Sensors::distress_beacon`] only checks where those edges cross.
+use crate::vector::Vec2;
+use anyhow::{ensure, Context, Error, Result};
+use regex::Regex;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+/// Inclusive range of columns
+pub type Interval = (isize, isize);
+
+/// Sort and join overlapping or touching intervals
+pub fn merge(mut intervals: Vec<Interval>) -> Vec<Interval> {
+    intervals.sort_unstable();
+    let mut merged: Vec<Interval> = Vec::with_capacity(intervals.len());
+    for (lo, hi) in intervals {
+        match merged.last_mut() {
+            Some(last) if lo <= last.1 + 1 => last.1 = last.1.max(hi),
+            _ => merged.push((lo, hi)),
+        }
+    }
+    merged
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sensor {
+    pub pos: Vec2,
+    /// The closest beacon
+    pub beacon: Vec2,
+    /// Distance to the closest beacon, there can't be any other beacon this close
+    pub radius: isize,
+}
+
+impl Sensor {
+    pub fn new(pos: Vec2, beacon: Vec2) -> Self {
+        Self {
+            pos,
+            beacon,
+            radius: pos.manhattan(beacon),
+        }
+    }
+
+    pub fn covers(&self, p: Vec2) -> bool {
+        self.pos.manhattan(p) <= self.radius
+    }
+
+    /// The columns covered on row `y`, if the diamond reaches it
+    pub fn span(&self, y: isize) -> Option<Interval> {
+        let half = self.radius - (y - self.pos.y).abs();
+        (half >= 0).then_some((self.pos.x - half, self.pos.x + half))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sensors {
+    sensors: Vec<Sensor>,
+}
+
+impl Sensors {
+    pub fn new(sensors: Vec<Sensor>) -> Self {
+        Self { sensors }
+    }
+
+    pub fn sensors(&self) -> &[Sensor] {
+        &self.sensors
+    }
+
+    pub fn covers(&self, p: Vec2) -> bool {
+        self.sensors.iter().any(|s| s.covers(p))
+    }
+
+    /// Every column on row `y` inside at least one diamond, as sorted disjoint intervals
+    pub fn coverage(&self, y: isize) -> Vec<Interval> {
+        merge(self.sensors.iter().filter_map(|s| s.span(y)).collect())
+    }
+
+    /// How many cells on row `y` can't hold a beacon. Known beacons on the row don't count.
+    pub fn excluded(&self, y: isize) -> usize {
+        let covered = self
+            .coverage(y)
+            .iter()
+            .map(|(lo, hi)| (hi - lo + 1) as usize)
+            .sum::<usize>();
+        let beacons = self
+            .sensors
+            .iter()
+            .filter(|s| s.beacon.y == y)
+            .map(|s| s.beacon.x)
+            .collect::<HashSet<_>>();
+        covered - beacons.len()
+    }
+
+    /// The leftmost column in `lo..=hi` on row `y` that no sensor covers
+    pub fn uncovered_in_row(&self, y: isize, lo: isize, hi: isize) -> Option<isize> {
+        let mut x = lo;
+        for (start, end) in self.coverage(y) {
+            if x < start {
+                break;
+            }
+            x = x.max(end + 1);
+        }
+        (x <= hi).then_some(x)
+    }
+
+    /// The first uncovered cell in the square from `(0, 0)` to `(max, max)`, scanning row by row
+    /// and skipping over whole intervals
+    pub fn scan(&self, max: isize) -> Option<Vec2> {
+        (0..=max).find_map(|y| self.uncovered_in_row(y, 0, max).map(|x| Vec2::new(x, y)))
+    }
+
+    /// An uncovered cell in the square from `(0, 0)` to `(max, max)`.
+    ///
+    /// A single uncovered cell surrounded by coverage lies one step outside the diamonds around
+    /// it, so it's where a rising edge of one meets a falling edge of another. Only those
+    /// crossings are checked, falling back to [`Sensors::scan`] for cells pinned against the
+    /// square's sides instead.
+    pub fn distress_beacon(&self, max: isize) -> Option<Vec2> {
+        // edges just outside each diamond, as x + y = c and x - y = c
+        let mut rising = HashSet::new();
+        let mut falling = HashSet::new();
+        for s in &self.sensors {
+            let r = s.radius + 1;
+            rising.insert(s.pos.x + s.pos.y - r);
+            rising.insert(s.pos.x + s.pos.y + r);
+            falling.insert(s.pos.x - s.pos.y - r);
+            falling.insert(s.pos.x - s.pos.y + r);
+        }
+        let in_square = |p: &Vec2| (0..=max).contains(&p.x) && (0..=max).contains(&p.y);
+        rising
+            .iter()
+            .flat_map(|&a| falling.iter().map(move |&b| (a, b)))
+            .filter(|(a, b)| (a - b) % 2 == 0)
+            .map(|(a, b)| Vec2::new((a + b) / 2, (a - b) / 2))
+            .filter(in_square)
+            .find(|&p| !self.covers(p))
+            .or_else(|| self.scan(max))
+    }
+}
+
+impl FromStr for Sensors {
+    type Err = Error;
+    /// `Sensor at x=2, y=18: closest beacon is at x=-2, y=15` on every line
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let re = Regex::new(r"-?\d+").expect("Invalid regex");
+        let sensors = s
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty())
+            .map(|(ln, line)| {
+                let values = re
+                    .find_iter(line)
+                    .map(|m| m.as_str().parse())
+                    .collect::<Result<Vec<isize>, _>>()
+                    .with_context(|| format!("Line {}: '{}'", ln + 1, line))?;
+                ensure!(
+                    values.len() == 4,
+                    "Line {}: expected a sensor and a beacon position, got '{}'",
+                    ln + 1,
+                    line
+                );
+                Ok(Sensor::new(
+                    Vec2::new(values[0], values[1]),
+                    Vec2::new(values[2], values[3]),
+                ))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { sensors })
+    }
+}
+
+/// The distress signal's tuning frequency for a position
+pub fn tuning_frequency(p: Vec2) -> isize {
+    p.x * 4_000_000 + p.y
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = include_str!("../inputs/day15/sample.txt");
+
+    #[test]
+    fn merging() {
+        assert_eq!(
+            merge(vec![(5, 8), (0, 2), (3, 4), (10, 12)]),
+            [(0, 8), (10, 12)]
+        );
+        assert_eq!(merge(vec![(0, 10), (2, 3)]), [(0, 10)]);
+        assert_eq!(merge(vec![]), []);
+    }
+
+    #[test]
+    fn spans() {
+        let s = Sensor::new(Vec2::new(8, 7), Vec2::new(2, 10));
+        assert_eq!(s.radius, 9);
+        assert_eq!(s.span(7), Some((-1, 17)));
+        assert_eq!(s.span(16), Some((8, 8)));
+        assert_eq!(s.span(17), None);
+    }
+
+    #[test]
+    fn sample_rows() {
+        let sensors = SAMPLE.parse::<Sensors>().unwrap();
+        assert_eq!(sensors.coverage(10), [(-2, 24)]);
+        assert_eq!(sensors.excluded(10), 26);
+        assert_eq!(sensors.uncovered_in_row(10, 0, 20), None);
+        assert_eq!(sensors.uncovered_in_row(11, 0, 20), Some(14));
+    }
+
+    #[test]
+    fn distress_beacon() {
+        let sensors = SAMPLE.parse::<Sensors>().unwrap();
+        assert_eq!(sensors.scan(20), Some(Vec2::new(14, 11)));
+        assert_eq!(sensors.distress_beacon(20), Some(Vec2::new(14, 11)));
+        assert_eq!(tuning_frequency(Vec2::new(14, 11)), 56000011);
+    }
+
+    #[test]
+    fn gap_in_a_corner() {
+        // only (0, 0) is left, and it's next to a single diamond
+        let sensors = Sensors::new(vec![Sensor::new(Vec2::new(3, 3), Vec2::new(3, 8))]);
+        assert_eq!(sensors.distress_beacon(3), Some(Vec2::new(0, 0)));
+    }
+
+    #[test]
+    fn bad_lines() {
+        assert!("Sensor at x=2, y=18: closest beacon is at x=-2"
+            .parse::<Sensors>()
+            .is_err());
+    }
+}
@@ -0,0 +1,115 @@
+//! A 2D integer vector for positions and offsets on grids.
+use std::fmt::Display;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Vec2 {
+    pub x: isize,
+    pub y: isize,
+}
+
+impl Vec2 {
+    pub const ZERO: Vec2 = Vec2::new(0, 0);
+    pub const UP: Vec2 = Vec2::new(0, 1);
+    pub const DOWN: Vec2 = Vec2::new(0, -1);
+    pub const LEFT: Vec2 = Vec2::new(-1, 0);
+    pub const RIGHT: Vec2 = Vec2::new(1, 0);
+
+    pub const fn new(x: isize, y: isize) -> Self {
+        Self { x, y }
+    }
+
+    /// Each component clamped to -1, 0 or 1
+    pub fn signum(self) -> Self {
+        Self::new(self.x.signum(), self.y.signum())
+    }
+
+    pub fn abs(self) -> Self {
+        Self::new(self.x.abs(), self.y.abs())
+    }
+
+    pub fn manhattan(self, other: Self) -> isize {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+
+    /// Distance when diagonal steps count as one, so touching points are at most 1 apart
+    pub fn chebyshev(self, other: Self) -> isize {
+        (self.x - other.x).abs().max((self.y - other.y).abs())
+    }
+}
+
+impl From<(isize, isize)> for Vec2 {
+    fn from((x, y): (isize, isize)) -> Self {
+        Self::new(x, y)
+    }
+}
+
+impl Display for Vec2 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl AddAssign for Vec2 {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl SubAssign for Vec2 {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+impl Mul<isize> for Vec2 {
+    type Output = Self;
+    fn mul(self, rhs: isize) -> Self {
+        Self::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic() {
+        let a = Vec2::new(3, -2);
+        assert_eq!(a + Vec2::UP * 4, Vec2::new(3, 2));
+        assert_eq!(a - a, Vec2::ZERO);
+        assert_eq!(-a, Vec2::new(-3, 2));
+        assert_eq!(a.signum(), Vec2::new(1, -1));
+        assert_eq!(Vec2::from((0, 5)).signum(), Vec2::UP);
+    }
+
+    #[test]
+    fn distances() {
+        let a = Vec2::new(1, 1);
+        let b = Vec2::new(3, 2);
+        assert_eq!(a.manhattan(b), 3);
+        assert_eq!(a.chebyshev(b), 2);
+        assert_eq!(a.chebyshev(a + Vec2::new(1, -1)), 1);
+    }
+}
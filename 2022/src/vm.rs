@@ -0,0 +1,413 @@
+//! A tiny cycle accurate virtual machine for the assembly style puzzles.
+//!
+//! An instruction set declares how many cycles each instruction takes and what it does to the
+//! registers once it completes, the [`Vm`] takes care of the clock, the instruction pointer,
+//! breakpoints, tracing and letting callers peek at the registers during any cycle.
+use crate::parse_error::ParseError;
+use anyhow::{bail, Context, Result};
+use std::fmt::{Debug, Display};
+use std::str::FromStr;
+
+/// What the instruction pointer does after an instruction completes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    /// Move on to the next instruction
+    Next,
+    /// Move the instruction pointer by this many instructions
+    Jump(isize),
+    /// Stop the machine
+    Halt,
+}
+
+pub trait InstructionSet: FromStr + Debug {
+    type Registers: Debug + Clone;
+
+    /// Number of cycles the instruction takes to complete, 0 is treated as 1
+    fn cycles(&self) -> usize;
+
+    /// Apply the instruction at the end of its last cycle
+    fn execute(&self, registers: &mut Self::Registers) -> Flow;
+}
+
+/// An instruction along with the line of the program it came from
+#[derive(Debug, Clone)]
+pub struct Line<I> {
+    pub instruction: I,
+    pub line_number: usize,
+}
+
+/// Parse one instruction per line, pointing at the line that failed
+pub fn parse_program<I: InstructionSet>(input: &str) -> Result<Vec<Line<I>>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(ln, l)| {
+            Ok(Line {
+                instruction: l
+                    .parse::<I>()
+                    .map_err(|_| ParseError::at_slice(input, l, "an instruction"))?,
+                line_number: ln + 1,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// Stop before this cycle starts
+    Cycle(usize),
+    /// Stop before the instruction at this address starts
+    Ip(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    Halted,
+    Breakpoint(Breakpoint),
+}
+
+/// The machine as seen during a single cycle
+#[derive(Debug, Clone)]
+pub struct Snapshot<R> {
+    /// 1-indexed cycle being executed
+    pub cycle: usize,
+    pub ip: usize,
+    pub registers: R,
+}
+
+#[derive(Debug)]
+pub struct Vm<I: InstructionSet> {
+    program: Vec<Line<I>>,
+    registers: I::Registers,
+    /// Number of completed cycles
+    cycle: usize,
+    ip: usize,
+    cycles_remaining: usize,
+    halted: bool,
+    breakpoints: Vec<Breakpoint>,
+    /// The number of completed cycles when the last breakpoint stopped the machine
+    stopped_at: Option<usize>,
+    trace: Option<Vec<Snapshot<I::Registers>>>,
+}
+
+impl<I: InstructionSet> Vm<I> {
+    pub fn new(program: Vec<Line<I>>, registers: I::Registers) -> Self {
+        let cycles_remaining = program.first().map_or(0, |l| l.instruction.cycles().max(1));
+        Self {
+            halted: program.is_empty(),
+            program,
+            registers,
+            cycle: 0,
+            ip: 0,
+            cycles_remaining,
+            breakpoints: vec![],
+            stopped_at: None,
+            trace: None,
+        }
+    }
+
+    pub fn registers(&self) -> &I::Registers {
+        &self.registers
+    }
+
+    /// Number of completed cycles
+    pub fn cycle(&self) -> usize {
+        self.cycle
+    }
+
+    pub fn ip(&self) -> usize {
+        self.ip
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.push(breakpoint);
+    }
+
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    /// Start recording a snapshot of every cycle from now on
+    pub fn enable_trace(&mut self) {
+        self.trace.get_or_insert_with(Vec::new);
+    }
+
+    pub fn trace(&self) -> &[Snapshot<I::Registers>] {
+        self.trace.as_deref().unwrap_or_default()
+    }
+
+    /// The state of the machine during the cycle that is about to run
+    pub fn snapshot(&self) -> Snapshot<I::Registers> {
+        Snapshot {
+            cycle: self.cycle + 1,
+            ip: self.ip,
+            registers: self.registers.clone(),
+        }
+    }
+
+    /// Cycles the instruction at `ip` takes, never less than 1
+    fn cycles_at(&self, ip: usize) -> usize {
+        self.program[ip].instruction.cycles().max(1)
+    }
+
+    fn starting_instruction(&self) -> bool {
+        self.cycles_remaining == self.cycles_at(self.ip)
+    }
+
+    fn breakpoint_hit(&self) -> Option<Breakpoint> {
+        self.breakpoints.iter().copied().find(|bp| match *bp {
+            Breakpoint::Cycle(c) => c == self.cycle + 1,
+            Breakpoint::Ip(ip) => ip == self.ip && self.starting_instruction(),
+        })
+    }
+
+    /// Run a single cycle
+    pub fn tick(&mut self) -> Result<()> {
+        if self.halted {
+            bail!("No more instructions to execute");
+        }
+        if let Some(trace) = self.trace.as_mut() {
+            trace.push(Snapshot {
+                cycle: self.cycle + 1,
+                ip: self.ip,
+                registers: self.registers.clone(),
+            });
+        }
+
+        self.cycle += 1;
+        self.cycles_remaining -= 1;
+        if self.cycles_remaining > 0 {
+            return Ok(());
+        }
+
+        let flow = self.program[self.ip]
+            .instruction
+            .execute(&mut self.registers);
+        let next = match flow {
+            Flow::Next => Some(self.ip + 1),
+            Flow::Jump(offset) => self.ip.checked_add_signed(offset),
+            Flow::Halt => None,
+        };
+        match next.filter(|&ip| ip < self.program.len()) {
+            Some(ip) => {
+                self.ip = ip;
+                self.cycles_remaining = self.cycles_at(ip);
+            }
+            None => self.halted = true,
+        }
+        Ok(())
+    }
+
+    /// Run `ticks` cycles
+    pub fn tick_n(&mut self, ticks: usize) -> Result<()> {
+        for _ in 0..ticks {
+            self.tick()
+                .with_context(|| format!("Tick {} times", ticks))?;
+        }
+        Ok(())
+    }
+
+    /// Run until the program halts or a breakpoint is hit. A breakpoint the machine is already
+    /// sitting on doesn't stop it again, so calling `run` repeatedly steps between breakpoints.
+    pub fn run(&mut self) -> Stop {
+        self.run_with(|_| {})
+    }
+
+    /// [`Vm::run`], handing `hook` a snapshot of every cycle before it runs
+    pub fn run_with(&mut self, mut hook: impl FnMut(&Snapshot<I::Registers>)) -> Stop {
+        while !self.halted {
+            if self.stopped_at != Some(self.cycle) {
+                if let Some(bp) = self.breakpoint_hit() {
+                    self.stopped_at = Some(self.cycle);
+                    return Stop::Breakpoint(bp);
+                }
+            }
+            hook(&self.snapshot());
+            self.tick().expect("machine is not halted");
+        }
+        Stop::Halted
+    }
+
+    /// Run until `cycle` has completed
+    pub fn run_to_cycle(&mut self, cycle: usize) -> Result<()> {
+        let ticks = cycle
+            .checked_sub(self.cycle)
+            .with_context(|| format!("Cycle {} has already run", cycle))?;
+        self.tick_n(ticks)
+    }
+
+    /// The state of the machine during `cycle`, leaving it just before `cycle` runs
+    pub fn during(&mut self, cycle: usize) -> Result<Snapshot<I::Registers>> {
+        if cycle == 0 {
+            bail!("Cycles start at 1");
+        }
+        self.run_to_cycle(cycle - 1)?;
+        if self.halted {
+            bail!("Program halted before cycle {}", cycle);
+        }
+        Ok(self.snapshot())
+    }
+}
+
+impl<I> Display for Vm<I>
+where
+    I: InstructionSet,
+    I::Registers: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "                       Cycle: {}", self.cycle)?;
+        writeln!(f, "                   Registers: {}", self.registers)?;
+        if let Some(line) = self.program.get(self.ip).filter(|_| !self.halted) {
+            writeln!(f, "                 Instruction: {:?}", line.instruction)?;
+            writeln!(f, "     Instruction Line Number: {}", line.line_number)?;
+            writeln!(f, "Instruction Cycles Remaining: {}", self.cycles_remaining)?;
+        } else {
+            writeln!(f, "                 Instruction: halted")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A counter machine: `inc` takes 1 cycle, `add n` takes 3 and `jnz n` jumps if acc != 0
+    #[derive(Debug)]
+    enum Op {
+        /// Takes no time at all
+        Nop,
+        Inc,
+        Add(isize),
+        Dec,
+        Jnz(isize),
+    }
+
+    impl FromStr for Op {
+        type Err = std::num::ParseIntError;
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let (op, arg) = s.split_once(' ').unwrap_or((s, "0"));
+            Ok(match op {
+                "nop" => Op::Nop,
+                "inc" => Op::Inc,
+                "dec" => Op::Dec,
+                "add" => Op::Add(arg.parse()?),
+                _ => Op::Jnz(arg.parse()?),
+            })
+        }
+    }
+
+    impl InstructionSet for Op {
+        type Registers = isize;
+        fn cycles(&self) -> usize {
+            match self {
+                Op::Add(_) => 3,
+                Op::Nop => 0,
+                _ => 1,
+            }
+        }
+        fn execute(&self, acc: &mut isize) -> Flow {
+            match self {
+                Op::Nop => {}
+                Op::Inc => *acc += 1,
+                Op::Dec => *acc -= 1,
+                Op::Add(n) => *acc += n,
+                Op::Jnz(n) if *acc != 0 => return Flow::Jump(*n),
+                Op::Jnz(_) => {}
+            }
+            Flow::Next
+        }
+    }
+
+    fn vm(program: &str) -> Vm<Op> {
+        Vm::new(parse_program(program).unwrap(), 0)
+    }
+
+    #[test]
+    fn multi_cycle_instructions() {
+        let mut vm = vm("inc\nadd 5\ninc");
+        assert_eq!(vm.during(2).unwrap().registers, 1);
+        assert_eq!(vm.during(4).unwrap().registers, 1);
+        assert_eq!(vm.during(5).unwrap().registers, 6);
+        assert_eq!(vm.run(), Stop::Halted);
+        assert_eq!((*vm.registers(), vm.cycle()), (7, 5));
+    }
+
+    #[test]
+    fn jumps_and_breakpoints() {
+        // count down from 3
+        let mut vm = vm("add 3\ndec\njnz -1\ninc");
+        vm.add_breakpoint(Breakpoint::Ip(1));
+        let mut seen = vec![];
+        while let Stop::Breakpoint(bp) = vm.run() {
+            assert_eq!(bp, Breakpoint::Ip(1));
+            seen.push(*vm.registers());
+        }
+        assert_eq!(seen, vec![3, 2, 1]);
+        assert_eq!(*vm.registers(), 1);
+    }
+
+    #[test]
+    fn cycle_breakpoint_and_trace() {
+        let mut vm = vm("inc\nadd 2\ninc");
+        vm.enable_trace();
+        vm.add_breakpoint(Breakpoint::Cycle(4));
+        assert_eq!(vm.run(), Stop::Breakpoint(Breakpoint::Cycle(4)));
+        assert_eq!(vm.cycle(), 3);
+        assert_eq!(vm.run(), Stop::Halted);
+        let trace = vm
+            .trace()
+            .iter()
+            .map(|s| (s.cycle, s.ip, s.registers))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            trace,
+            vec![(1, 0, 0), (2, 1, 1), (3, 1, 1), (4, 1, 1), (5, 2, 3)]
+        );
+    }
+
+    #[test]
+    fn breakpoints_before_the_first_run() {
+        let mut by_cycle = vm("inc\ninc");
+        by_cycle.add_breakpoint(Breakpoint::Cycle(1));
+        assert_eq!(by_cycle.run(), Stop::Breakpoint(Breakpoint::Cycle(1)));
+        assert_eq!(by_cycle.cycle(), 0);
+        assert_eq!(by_cycle.run(), Stop::Halted);
+
+        let mut by_ip = vm("inc\ninc");
+        by_ip.add_breakpoint(Breakpoint::Ip(0));
+        assert_eq!(by_ip.run(), Stop::Breakpoint(Breakpoint::Ip(0)));
+        assert_eq!(by_ip.run(), Stop::Halted);
+        assert_eq!(*by_ip.registers(), 2);
+    }
+
+    #[test]
+    fn zero_cycle_instructions_take_one() {
+        let mut machine = vm("nop\ninc\nnop");
+        assert_eq!(machine.run(), Stop::Halted);
+        assert_eq!((*machine.registers(), machine.cycle()), (1, 3));
+        assert_eq!(vm("nop").during(1).unwrap().ip, 0);
+    }
+
+    #[test]
+    fn hooks_see_every_cycle() {
+        let mut vm = vm("add 2\ninc");
+        let mut during = vec![];
+        vm.run_with(|s| during.push(s.registers));
+        assert_eq!(during, vec![0, 0, 0, 2]);
+    }
+
+    #[test]
+    fn parse_errors_point_at_the_line() {
+        let e = parse_program::<Op>("inc\nadd x").unwrap_err();
+        assert_eq!((e.line, e.column), (2, 1));
+        assert_eq!(
+            e.to_string(),
+            "expected an instruction at line 2, column 1\n  |\n2 | add x\n  | ^"
+        );
+    }
+}
@@ -2,7 +2,8 @@
 extern crate test;
 use aoc_2023::*;
 
-use itertools::{Itertools, Position};
+use cards::Rules;
+use itertools::Itertools;
 
 const DAY: u8 = 7;
 
@@ -16,81 +17,17 @@ fn main() {
     );
 }
 
-#[derive(Copy, Clone)]
-enum FaceRank {
-    Wild = 1,
-    Ten = 10,
-    Jack,
-    Queen,
-    King,
-    Ace,
-}
-
-impl FaceRank {
-    fn as_u8(&self) -> u8 {
-        *self as u8
-    }
-}
-
 #[derive(Eq, PartialEq, Debug)]
 struct Hand {
     bid: u64,
-    cards: Vec<u8>,
-}
-
-#[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
-enum HandType {
-    HighCard,
-    OnePair,
-    TwoPair,
-    ThreeOfKind,
-    FullHouse,
-    FourOfKind,
-    FiveOfKind,
+    cards: String,
 }
 
 impl Hand {
-    fn new(cards: Vec<u8>, bid: u64) -> Self {
-        Self { cards, bid }
-    }
-
-    fn jacks_to_wild(&mut self) {
-        for c in self
-            .cards
-            .iter_mut()
-            .filter(|c| **c == FaceRank::Jack.as_u8())
-        {
-            *c = FaceRank::Wild.as_u8();
-        }
-    }
-
-    fn hand_type(&self) -> HandType {
-        use HandType::*;
-        let mut map = self.cards.iter().counts();
-        let wilds = map.remove(&FaceRank::Wild.as_u8()).unwrap_or_default();
-
-        if wilds == 5 {
-            return FiveOfKind;
-        }
-
-        let counts = map
-            .values()
-            .sorted()
-            .with_position()
-            .map(|(p, n)| match p {
-                Position::Last | Position::Only => n + wilds,
-                _ => *n,
-            })
-            .join("");
-
-        match counts.as_str() {
-            "5" => FiveOfKind,
-            "14" => FourOfKind,
-            "23" => FullHouse,
-            "113" => ThreeOfKind,
-            "122" => TwoPair,
-            "1112" => OnePair,
-            _ => HighCard,
+    fn new(cards: &str, bid: u64) -> Self {
+        Self {
+            cards: cards.to_string(),
+            bid,
         }
     }
 }
@@ -98,48 +35,29 @@ impl Hand {
 fn parse(input: &str) -> impl Iterator<Item = Hand> + '_ {
     input.lines().map(|line| {
         let (cards, bid) = line.split_once(' ').unwrap();
-        let cards = cards
-            .chars()
-            .map(|c| {
-                c.to_digit(10).unwrap_or_else(|| match c {
-                    'A' => FaceRank::Ace as u32,
-                    'K' => FaceRank::King as u32,
-                    'Q' => FaceRank::Queen as u32,
-                    'J' => FaceRank::Jack as u32,
-                    'T' => FaceRank::Ten as u32,
-                    _ => unreachable!(),
-                }) as u8
-            })
-            .collect();
-
-        let bid = bid.parse().unwrap();
-        Hand::new(cards, bid)
+        Hand::new(cards, bid.parse().unwrap())
     })
 }
 
+fn total_winnings(input: &str, rules: &Rules) -> u64 {
+    parse(input)
+        .sorted_by_cached_key(|h| rules.strength(&h.cards).expect("Unknown card"))
+        .enumerate()
+        .map(|(i, hand)| (i as u64 + 1) * hand.bid)
+        .sum()
+}
+
 pub mod p1 {
     use super::*;
     pub fn solve(input: &str) -> u64 {
-        parse(input)
-            .sorted_by_key(|h| (h.hand_type(), h.cards.clone()))
-            .enumerate()
-            .map(|(i, hand)| (i as u64 + 1) * hand.bid)
-            .sum()
+        total_winnings(input, &Rules::camel())
     }
 }
 
 pub mod p2 {
     use super::*;
     pub fn solve(input: &str) -> u64 {
-        parse(input)
-            .map(|mut h| {
-                h.jacks_to_wild();
-                h
-            })
-            .sorted_by_key(|h| (h.hand_type(), h.cards.clone()))
-            .enumerate()
-            .map(|(i, hand)| (i as u64 + 1) * hand.bid)
-            .sum()
+        total_winnings(input, &Rules::camel_jokers())
     }
 }
 
@@ -153,11 +71,11 @@ mod day07_tests {
     fn test_parse_sample() {
         let hands = parse(SAMPLE).collect::<Vec<_>>();
         let e = vec![
-            Hand::new(vec![3, 2, 10, 3, 13], 765),
-            Hand::new(vec![10, 5, 5, 11, 5], 684),
-            Hand::new(vec![13, 13, 6, 7, 7], 28),
-            Hand::new(vec![13, 10, 11, 11, 10], 220),
-            Hand::new(vec![12, 12, 12, 11, 14], 483),
+            Hand::new("32T3K", 765),
+            Hand::new("T55J5", 684),
+            Hand::new("KK677", 28),
+            Hand::new("KTJJT", 220),
+            Hand::new("QQQJA", 483),
         ];
         assert_eq!(hands, e);
     }
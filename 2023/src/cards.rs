@@ -0,0 +1,289 @@
+//! Ranking hands of cards, as in Camel Cards (day 7) and poker without suits.
+//!
+//! [`Rules`] decide the order of the cards, which card (if any) is wild and how hands of the same
+//! type are told apart. A wild card always counts as whatever card makes the best hand type.
+use anyhow::{bail, Result};
+use itertools::Itertools;
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HandType {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfKind,
+    FullHouse,
+    FourOfKind,
+    FiveOfKind,
+}
+
+impl HandType {
+    /// The type of a hand with these group sizes, largest first
+    fn from_groups(groups: &[usize]) -> Self {
+        use HandType::*;
+        match groups {
+            [n, ..] if *n >= 5 => FiveOfKind,
+            [4, ..] => FourOfKind,
+            [3, n, ..] if *n >= 2 => FullHouse,
+            [3, ..] => ThreeOfKind,
+            [2, 2, ..] => TwoPair,
+            [2, ..] => OnePair,
+            _ => HighCard,
+        }
+    }
+}
+
+/// How two hands of the same type are ordered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreak {
+    /// Compare the cards in the order they were dealt, like Camel Cards
+    Positional,
+    /// Pick the best five cards and compare the largest groups first, then the kickers, like poker
+    BestFive,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    /// Every card, weakest first
+    order: Vec<char>,
+    wild: Option<char>,
+    tie_break: TieBreak,
+}
+
+/// A hand's type along with the card ranks used to break ties
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Strength {
+    pub hand_type: HandType,
+    pub ranks: Vec<u8>,
+}
+
+impl Rules {
+    /// `order` lists every card from weakest to strongest
+    pub fn new(order: &str, wild: Option<char>, tie_break: TieBreak) -> Result<Self> {
+        let order = order.chars().collect::<Vec<_>>();
+        if !order.iter().all_unique() {
+            bail!("Cards can only appear once in the order");
+        }
+        if let Some(w) = wild.filter(|w| !order.contains(w)) {
+            bail!("The wild card '{}' isn't in the order", w);
+        }
+        Ok(Self {
+            order,
+            wild,
+            tie_break,
+        })
+    }
+
+    /// Camel Cards without jokers
+    pub fn camel() -> Self {
+        Self::new("23456789TJQKA", None, TieBreak::Positional).unwrap()
+    }
+
+    /// Camel Cards where `J` is a joker, the weakest card on its own
+    pub fn camel_jokers() -> Self {
+        Self::new("J23456789TQKA", Some('J'), TieBreak::Positional).unwrap()
+    }
+
+    /// Suitless poker, Aces high
+    pub fn poker() -> Self {
+        Self::new("23456789TJQKA", None, TieBreak::BestFive).unwrap()
+    }
+
+    pub fn with_wild(mut self, wild: Option<char>) -> Result<Self> {
+        if let Some(w) = wild.filter(|w| !self.order.contains(w)) {
+            bail!("The wild card '{}' isn't in the order", w);
+        }
+        self.wild = wild;
+        Ok(self)
+    }
+
+    pub fn rank(&self, card: char) -> Option<u8> {
+        self.order.iter().position(|&c| c == card).map(|r| r as u8)
+    }
+
+    fn ranks(&self, cards: &str) -> Result<Vec<u8>> {
+        cards
+            .chars()
+            .map(|c| match self.rank(c) {
+                Some(r) => Ok(r),
+                None => bail!("Unknown card '{}' in '{}'", c, cards),
+            })
+            .collect()
+    }
+
+    /// Groups of equal non-wild cards as (size, rank), largest then strongest first, with the
+    /// wild cards joining the first group
+    fn groups(&self, ranks: &[u8]) -> Vec<(usize, u8)> {
+        let wild = self.wild.and_then(|w| self.rank(w));
+        let wilds = ranks.iter().filter(|&&r| Some(r) == wild).count();
+        let mut groups = ranks
+            .iter()
+            .filter(|&&r| Some(r) != wild)
+            .counts()
+            .into_iter()
+            .map(|(&r, n)| (n, r))
+            .sorted_by(|a, b| b.cmp(a))
+            .collect::<Vec<_>>();
+        match groups.first_mut() {
+            Some(first) => first.0 += wilds,
+            // all wild, so all the strongest card
+            None if wilds > 0 => groups.push((wilds, self.order.len() as u8 - 1)),
+            None => {}
+        }
+        groups
+    }
+
+    pub fn hand_type(&self, cards: &str) -> Result<HandType> {
+        Ok(self.strength(cards)?.hand_type)
+    }
+
+    pub fn strength(&self, cards: &str) -> Result<Strength> {
+        let ranks = self.ranks(cards)?;
+        let groups = self.groups(&ranks);
+        Ok(match self.tie_break {
+            TieBreak::Positional => Strength {
+                hand_type: HandType::from_groups(&groups.iter().map(|g| g.0).collect_vec()),
+                ranks,
+            },
+            TieBreak::BestFive => {
+                let best = best_five(&groups);
+                Strength {
+                    hand_type: HandType::from_groups(&best.iter().map(|g| g.0).collect_vec()),
+                    ranks: best
+                        .iter()
+                        .flat_map(|&(n, r)| std::iter::repeat_n(r, n))
+                        .collect(),
+                }
+            }
+        })
+    }
+
+    pub fn compare(&self, a: &str, b: &str) -> Result<Ordering> {
+        Ok(self.strength(a)?.cmp(&self.strength(b)?))
+    }
+}
+
+/// Keep pairs or better while at least two cards are left to fill, cutting down groups that
+/// don't fit, then fill up with the strongest of the cards left over
+fn best_five(groups: &[(usize, u8)]) -> Vec<(usize, u8)> {
+    let mut best = vec![];
+    let mut left = 5;
+    let mut kickers = vec![];
+    for &(n, r) in groups {
+        let take = if n >= 2 && left >= 2 { n.min(left) } else { 0 };
+        if take > 0 {
+            best.push((take, r));
+            left -= take;
+        }
+        kickers.extend(std::iter::repeat_n(r, n - take));
+    }
+    kickers.sort_by(|a, b| b.cmp(a));
+    best.extend(kickers.into_iter().take(left).map(|r| (1, r)));
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use HandType::*;
+
+    #[test]
+    fn camel_cards() {
+        let rules = Rules::camel();
+        assert_eq!(rules.hand_type("32T3K").unwrap(), OnePair);
+        assert_eq!(rules.hand_type("KTJJT").unwrap(), TwoPair);
+        assert_eq!(rules.hand_type("T55J5").unwrap(), ThreeOfKind);
+        assert_eq!(rules.compare("KK677", "KTJJT").unwrap(), Ordering::Greater);
+        assert_eq!(rules.compare("33332", "2AAAA").unwrap(), Ordering::Greater);
+
+        let jokers = Rules::camel_jokers();
+        assert_eq!(jokers.hand_type("KTJJT").unwrap(), FourOfKind);
+        assert_eq!(jokers.hand_type("JJJJJ").unwrap(), FiveOfKind);
+        assert_eq!(jokers.compare("JKKK2", "QQQQ2").unwrap(), Ordering::Less);
+    }
+
+    #[test]
+    fn any_card_can_be_wild() {
+        let rules = Rules::camel().with_wild(Some('2')).unwrap();
+        assert_eq!(rules.hand_type("22AKQ").unwrap(), ThreeOfKind);
+        // wild cards keep their place in the order
+        assert_eq!(rules.compare("2AAAA", "33332").unwrap(), Ordering::Less);
+        assert!(Rules::camel().with_wild(Some('X')).is_err());
+        assert!(Rules::camel().hand_type("32X3K").is_err());
+    }
+
+    #[test]
+    fn best_five_breaks_ties_by_groups() {
+        let rules = Rules::poker();
+        // a full house is compared on the three of a kind first
+        assert_eq!(rules.compare("33322", "22AAA").unwrap(), Ordering::Less);
+        assert_eq!(rules.compare("T9A8A", "AAKQ2").unwrap(), Ordering::Less);
+        // the best five of seven cards, the third pair only counts as a kicker
+        let s = rules.strength("KK99772").unwrap();
+        assert_eq!(s.hand_type, TwoPair);
+        assert_eq!(s.ranks, vec![11, 11, 7, 7, 5]);
+        // groups too big for what's left are cut down rather than dropped
+        assert_eq!(rules.hand_type("AAAKKK2").unwrap(), FullHouse);
+        assert_eq!(rules.hand_type("AAAAAAK").unwrap(), FiveOfKind);
+        // wild cards become whatever helps most
+        let wild = Rules::poker().with_wild(Some('J')).unwrap();
+        assert_eq!(
+            wild.strength("JA2A2").unwrap().ranks,
+            vec![12, 12, 12, 0, 0]
+        );
+        assert_eq!(wild.hand_type("JJJJ22K").unwrap(), FiveOfKind);
+    }
+
+    fn hand(
+        order: &'static str,
+        size: impl Into<proptest::collection::SizeRange>,
+    ) -> impl Strategy<Value = String> {
+        proptest::collection::vec(proptest::sample::select(order.chars().collect_vec()), size)
+            .prop_map(|cards| cards.into_iter().collect())
+    }
+
+    proptest! {
+        #[test]
+        fn wild_cards_make_the_best_hand(cards in hand("J23456789TQKA", 5)) {
+            let wild = Rules::camel_jokers();
+            let plain = Rules::camel();
+            let best = "23456789TQKA"
+                .chars()
+                .map(|sub| plain.hand_type(&cards.replace('J', &sub.to_string())).unwrap())
+                .max()
+                .unwrap();
+            prop_assert_eq!(wild.hand_type(&cards).unwrap(), best);
+        }
+
+        #[test]
+        fn wild_cards_never_hurt(cards in hand("23456789TJQKA", 5), wild in proptest::sample::select("23456789TJQKA".chars().collect_vec())) {
+            let rules = Rules::poker();
+            let wild = rules.clone().with_wild(Some(wild)).unwrap();
+            prop_assert!(wild.hand_type(&cards).unwrap() >= rules.hand_type(&cards).unwrap());
+        }
+
+        #[test]
+        fn ordering_is_antisymmetric(a in hand("23456789TJQKA", 5), b in hand("23456789TJQKA", 5)) {
+            for rules in [Rules::camel(), Rules::camel_jokers(), Rules::poker()] {
+                let ab = rules.compare(&a, &b).unwrap();
+                prop_assert_eq!(ab.reverse(), rules.compare(&b, &a).unwrap());
+            }
+        }
+
+        #[test]
+        fn best_five_beats_every_five(
+            cards in hand("23456789TJQKA", 6..=7),
+            wild in proptest::option::of(proptest::sample::select("23456789TJQKA".chars().collect_vec())),
+        ) {
+            let rules = Rules::poker().with_wild(wild).unwrap();
+            let best = cards
+                .chars()
+                .combinations(5)
+                .map(|five| rules.strength(&five.into_iter().collect::<String>()).unwrap())
+                .max()
+                .unwrap();
+            prop_assert!(rules.strength(&cards).unwrap() >= best);
+        }
+    }
+}
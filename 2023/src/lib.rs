@@ -1,3 +1,5 @@
+pub mod cards;
+
 use std::collections::HashSet;
 use std::fs;
 use std::io::read_to_string;
@@ -1,38 +1,40 @@
 #![feature(test)]
 extern crate test;
-use aoc_2024::*;
-use nom::{character::complete as character, multi::*, sequence::*, IResult};
+use aoc_2024::{parsers::*, *};
+use nom::{character::complete as character, error::VerboseError, sequence::*, IResult};
 use std::collections::HashMap;
 
 const DAY: u8 = 1;
 
-fn main() {
+fn main() -> anyhow::Result<()> {
     let input = &read_input(DAY);
     println!(
         "Day {:0>2}: Part 1 answer = {}, Part 2 answer = {}",
         DAY,
-        solve_p1(input),
-        solve_p2(input)
+        solve_p1(input)?,
+        solve_p2(input)?
     );
+    Ok(())
 }
 
-fn parse(s: &str) -> IResult<&str, Vec<(u64, u64)>> {
-    separated_list1(
-        character::line_ending,
-        separated_pair(character::u64, character::space1, character::u64),
-    )(s)
+fn parse(s: &str) -> IResult<&str, Vec<(u64, u64)>, VerboseError<&str>> {
+    lines_of(separated_pair(
+        character::u64,
+        character::space1,
+        character::u64,
+    ))(s)
 }
 
-pub fn solve_p1(input: &str) -> u64 {
-    let (mut a, mut b): (Vec<_>, Vec<_>) = parse(input).unwrap().1.into_iter().unzip();
+pub fn solve_p1(input: &str) -> Result<u64, ParseError> {
+    let (mut a, mut b): (Vec<_>, Vec<_>) = parse_all(input, parse)?.into_iter().unzip();
     a.sort();
     b.sort();
 
-    a.into_iter().zip(b).map(|(a, b)| a.abs_diff(b)).sum()
+    Ok(a.into_iter().zip(b).map(|(a, b)| a.abs_diff(b)).sum())
 }
 
-pub fn solve_p2(input: &str) -> u64 {
-    let (left, right): (Vec<_>, Vec<_>) = parse(input).unwrap().1.into_iter().unzip();
+pub fn solve_p2(input: &str) -> Result<u64, ParseError> {
+    let (left, right): (Vec<_>, Vec<_>) = parse_all(input, parse)?.into_iter().unzip();
 
     let multipliers_map =
         right
@@ -42,9 +44,10 @@ pub fn solve_p2(input: &str) -> u64 {
                 acc
             });
 
-    left.into_iter()
+    Ok(left
+        .into_iter()
         .map(|n| n * multipliers_map.get(&n).unwrap_or(&0u64))
-        .sum()
+        .sum())
 }
 
 #[cfg(test)]
@@ -55,24 +58,24 @@ mod day01_tests {
 
     #[test]
     fn p1_sample() {
-        assert_eq!(solve_p1(SAMPLE), 11)
+        assert_eq!(solve_p1(SAMPLE), Ok(11))
     }
 
     #[test]
     fn p1_input() {
         let input = &read_input(DAY);
-        assert_eq!(solve_p1(input), 2000468)
+        assert_eq!(solve_p1(input), Ok(2000468))
     }
 
     #[test]
     fn p2_sample() {
-        assert_eq!(solve_p2(SAMPLE), 31)
+        assert_eq!(solve_p2(SAMPLE), Ok(31))
     }
 
     #[test]
     fn p2_input() {
         let input = &read_input(DAY);
-        assert_eq!(solve_p2(input), 18567089)
+        assert_eq!(solve_p2(input), Ok(18567089))
     }
 }
 
@@ -1,44 +1,42 @@
 #![feature(test)]
 #![feature(array_windows)]
 extern crate test;
-use aoc_2024::*;
-use nom::{character::complete as character, multi::*, IResult};
+use aoc_2024::{parsers::*, *};
+use nom::{error::VerboseError, IResult};
 
 const DAY: u8 = 2;
 
-fn main() {
+fn main() -> anyhow::Result<()> {
     let input = &read_input(DAY);
     println!(
         "Day {:0>2}: Part 1 answer = {}, Part 2 answer = {}",
         DAY,
-        solve_p1(input),
-        solve_p2(input)
+        solve_p1(input)?,
+        solve_p2(input)?
     );
+    Ok(())
 }
 
-fn parse(s: &str) -> IResult<&str, Vec<Vec<i64>>> {
-    separated_list1(
-        character::line_ending,
-        separated_list1(character::space1, character::i64),
-    )(s)
+fn parse(s: &str) -> IResult<&str, Vec<Vec<i64>>, VerboseError<&str>> {
+    lines_of(signed_list)(s)
 }
 
-pub fn solve_p1(input: &str) -> usize {
-    let levels = parse(input).unwrap().1;
+pub fn solve_p1(input: &str) -> Result<usize, ParseError> {
+    let levels = parse_all(input, parse)?;
 
-    levels
+    Ok(levels
         .into_iter()
         .filter(|level| {
             let differences = diffs(level);
             is_safe(&differences)
         })
-        .count()
+        .count())
 }
 
-pub fn solve_p2(input: &str) -> usize {
-    let levels = parse(input).unwrap().1;
+pub fn solve_p2(input: &str) -> Result<usize, ParseError> {
+    let levels = parse_all(input, parse)?;
 
-    levels
+    Ok(levels
         .into_iter()
         .filter(|level| {
             let differences = diffs(level);
@@ -50,7 +48,7 @@ pub fn solve_p2(input: &str) -> usize {
                     is_safe(&differences)
                 })
         })
-        .count()
+        .count())
 }
 
 fn diffs(level: &[i64]) -> Vec<i64> {
@@ -73,24 +71,24 @@ mod day02_tests {
 
     #[test]
     fn p1_sample() {
-        assert_eq!(solve_p1(SAMPLE), 2)
+        assert_eq!(solve_p1(SAMPLE), Ok(2))
     }
 
     #[test]
     fn p1_input() {
         let input = &read_input(DAY);
-        assert_eq!(solve_p1(input), 598)
+        assert_eq!(solve_p1(input), Ok(598))
     }
 
     #[test]
     fn p2_sample() {
-        assert_eq!(solve_p2(SAMPLE), 4)
+        assert_eq!(solve_p2(SAMPLE), Ok(4))
     }
 
     #[test]
     fn p2_input() {
         let input = &read_input(DAY);
-        assert_eq!(solve_p2(input), 634)
+        assert_eq!(solve_p2(input), Ok(634))
     }
 }
 
@@ -1,12 +1,7 @@
 #![feature(test)]
 extern crate test;
+use aoc_2024::scanner::*;
 use aoc_2024::*;
-use nom::{
-    branch::*, bytes::complete as bytes, character::complete as character, multi::*, sequence::*,
-    IResult, Parser,
-};
-use nom_supreme::ParserExt;
-use regex::Regex;
 
 const DAY: u8 = 3;
 
@@ -20,59 +15,24 @@ fn main() {
     );
 }
 
-pub fn solve_p1(input: &str) -> i64 {
-    let r = Regex::new(r"mul\((\d+),(\d+)\)").unwrap();
-    r.captures_iter(input)
-        .map(|c| c.extract().1)
-        .map(|s: [&str; 2]| {
-            let a = s[0].parse::<i64>().unwrap();
-            let b = s[1].parse::<i64>().unwrap();
-            a * b
-        })
-        .sum()
+fn mul() -> Pattern {
+    Pattern::new("mul", 2).with_max_digits(3)
 }
 
-#[derive(Debug)]
-enum Instruction {
-    Mul(i64, i64),
-    Do,
-    Dont,
+fn product(call: Call) -> i64 {
+    call.args.iter().product::<u64>() as i64
 }
 
-fn parse(s: &str) -> IResult<&str, Vec<Instruction>> {
-    many1(alt((
-        bytes::tag("mul(")
-            .precedes(separated_pair(
-                character::i64,
-                bytes::tag(","),
-                character::i64,
-            ))
-            .terminated(bytes::tag(")"))
-            .map(|s| Some(Instruction::Mul(s.0, s.1))),
-        bytes::tag("don't").map(|_| Some(Instruction::Dont)),
-        bytes::tag("do").map(|_| Some(Instruction::Do)),
-        bytes::take(1usize).map(|_| None),
-    )))(s)
-    .map(|x| (x.0, x.1.into_iter().flatten().collect()))
+pub fn solve_p1(input: &str) -> i64 {
+    Scanner::new(vec![mul()]).scan(input).map(product).sum()
 }
-pub fn solve_p2(input: &str) -> i64 {
-    let instrucitons = parse(input).unwrap().1;
 
-    let mut do_flag = true;
-    let mut acc = 0;
-    for i in instrucitons.iter() {
-        match *i {
-            Instruction::Dont => do_flag = false,
-            Instruction::Do => do_flag = true,
-            Instruction::Mul(a, b) => {
-                if do_flag {
-                    acc += a * b
-                }
-            }
-        }
-    }
-
-    acc
+pub fn solve_p2(input: &str) -> i64 {
+    Scanner::new(vec![mul()])
+        .with_switches("don't", "do")
+        .enabled(input)
+        .map(product)
+        .sum()
 }
 
 #[cfg(test)]
@@ -1,5 +1,6 @@
 #![feature(test)]
 extern crate test;
+use aoc_2024::word_search::*;
 use aoc_2024::*;
 
 const DAY: u8 = 4;
@@ -15,117 +16,14 @@ fn main() {
 }
 
 pub fn solve_p1(input: &str) -> usize {
-    let mut acc = 0;
-
-    let grid = input.lines().map(|l| l.as_bytes()).collect::<Vec<_>>();
-
-    for i in 0..grid.len() {
-        for j in 0..grid[0].len() {
-            // if i >= 3 then can look up
-            // if j >= 3 then can look left
-            // if i <= grid.len() - 4 then can look down
-            // if j <= grid[0].len() - 4 then can look right
-
-            // look up
-            if i >= 3 {
-                let s: String = (0..4).map(|offset| grid[i - offset][j] as char).collect();
-                if s == "XMAS" {
-                    acc += 1;
-                }
-            }
-            // look left
-            if j >= 3 {
-                let s: String = (0..4).map(|offset| grid[i][j - offset] as char).collect();
-                if s == "XMAS" {
-                    acc += 1;
-                }
-            }
-            // look down
-            if i <= grid.len() - 4 {
-                let s: String = (0..4).map(|offset| grid[i + offset][j] as char).collect();
-                if s == "XMAS" {
-                    acc += 1;
-                }
-            }
-            // look right
-            if j <= grid[0].len() - 4 {
-                let s: String = (0..4).map(|offset| grid[i][j + offset] as char).collect();
-                if s == "XMAS" {
-                    acc += 1;
-                }
-            }
-            // look up_right
-            if i >= 3 && j <= grid[0].len() - 4 {
-                let s: String = (0..4)
-                    .map(|offset| grid[i - offset][j + offset] as char)
-                    .collect();
-                if s == "XMAS" {
-                    acc += 1;
-                }
-            }
-            // look up_left
-            if i >= 3 && j >= 3 {
-                let s: String = (0..4)
-                    .map(|offset| grid[i - offset][j - offset] as char)
-                    .collect();
-                if s == "XMAS" {
-                    acc += 1;
-                }
-            }
-            // look down_right
-            if i <= grid.len() - 4 && j <= grid[0].len() - 4 {
-                let s: String = (0..4)
-                    .map(|offset| grid[i + offset][j + offset] as char)
-                    .collect();
-                if s == "XMAS" {
-                    acc += 1;
-                }
-            }
-            // look down_left
-            if i <= grid.len() - 4 && j >= 3 {
-                let s: String = (0..4)
-                    .map(|offset| grid[i + offset][j - offset] as char)
-                    .collect();
-                if s == "XMAS" {
-                    acc += 1;
-                }
-            }
-        }
-    }
-    acc
+    WordSearch::new(input).find("XMAS", &Direction::ALL).count()
 }
 
 pub fn solve_p2(input: &str) -> usize {
-    let mut acc = 0;
-
-    let grid = input.lines().map(|l| l.as_bytes()).collect::<Vec<_>>();
-
-    for i in 0..grid.len() {
-        if i == 0 || i == grid.len() - 1 {
-            continue;
-        }
-        for j in 0..grid[0].len() {
-            if grid[i][j] != b'A' || j == 0 || j == grid[0].len() - 1 {
-                continue;
-            }
-
-            #[rustfmt::skip]
-            // negative diagonal
-            let a: String = [grid[i-1][j-1] as char, grid[i][j] as char, grid[i+1][j+1] as char].into_iter().collect();
-            #[rustfmt::skip]
-            let a2: String = [grid[i-1][j-1] as char, grid[i][j] as char, grid[i+1][j+1] as char].into_iter().rev().collect();
-            // positive diagonal
-            #[rustfmt::skip]
-            let b: String = [grid[i+1][j-1] as char, grid[i][j] as char, grid[i-1][j+1] as char].into_iter().collect();
-            #[rustfmt::skip]
-            let b2: String = [grid[i+1][j-1] as char, grid[i][j] as char, grid[i-1][j+1] as char].into_iter().rev().collect();
-
-            if (a == "MAS" || a2 == "MAS") && (b == "MAS" || b2 == "MAS") {
-                acc += 1
-            }
-        }
-    }
-    acc
+    let cross: Stencil = "M.S\n.A.\nM.S".parse().unwrap();
+    WordSearch::new(input)
+        .find_stencils(&cross.variants(true))
+        .len()
 }
 
 #[cfg(test)]
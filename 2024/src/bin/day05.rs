@@ -1,119 +1,63 @@
 #![feature(test)]
 extern crate test;
-use aoc_2024::*;
+use aoc_2024::{ordering::*, parsers::*, *};
 use nom::{
-    bytes::complete as bytes, character::complete as character, multi::*, sequence::*, IResult,
-    Parser,
+    bytes::complete as bytes, character::complete as character, error::context,
+    error::VerboseError, multi::*, sequence::*, IResult, Parser,
 };
-use nom_supreme::ParserExt;
-use std::collections::{HashMap, HashSet};
 
 const DAY: u8 = 5;
 
-fn main() {
+fn main() -> anyhow::Result<()> {
     let input = &read_input(DAY);
     println!(
         "Day {:0>2}: Part 1 answer = {}, Part 2 answer = {}",
         DAY,
-        solve_p1(input),
-        solve_p2(input)
+        solve_p1(input)?,
+        solve_p2(input)?
     );
+    Ok(())
 }
 
 type Updates = Vec<u32>;
-type OrderingMap = HashMap<u32, Vec<u32>>;
 
-#[allow(clippy::unwrap_or_default)]
-fn parse(s: &str) -> IResult<&str, (OrderingMap, Vec<Updates>)> {
+fn parse(s: &str) -> IResult<&str, (Precedence<u32>, Vec<Updates>), VerboseError<&str>> {
     separated_pair(
-        separated_list1(
-            character::line_ending,
+        lines_of(context(
+            "page ordering rule",
             separated_pair(character::u32, bytes::tag("|"), character::u32),
-        )
-        .map(|pairs| {
-            pairs.into_iter().fold(HashMap::new(), |mut acc, (a, b)| {
-                acc.entry(b).or_insert(vec![]).push(a);
-                acc
-            })
-        }),
-        character::line_ending.terminated(character::line_ending),
-        separated_list1(
-            character::line_ending,
+        ))
+        .map(Precedence::from_iter),
+        pair(character::line_ending, character::line_ending),
+        lines_of(context(
+            "update",
             separated_list1(bytes::tag(","), character::u32),
-        ),
+        )),
     )(s)
 }
 
-pub fn solve_p1(input: &str) -> u32 {
-    let (map, update_list) = parse(input).unwrap().1;
+pub fn solve_p1(input: &str) -> Result<u32, ParseError> {
+    let (rules, update_list) = parse_all(input, parse)?;
 
-    update_list
+    Ok(update_list
         .into_iter()
-        .filter_map(|updates| {
-            let mut well_ordered = true;
-            let mut disallowed = HashSet::new();
-
-            for update in updates.iter() {
-                if disallowed.contains(update) {
-                    well_ordered = false;
-                    break;
-                }
-                if let Some(xs) = map.get(update) {
-                    for x in xs {
-                        disallowed.insert(x);
-                    }
-                }
-            }
-
-            if well_ordered {
-                Some(updates[updates.len() / 2])
-            } else {
-                None
-            }
-        })
-        .sum()
+        .filter(|updates| rules.is_ordered(updates))
+        .map(|updates| updates[updates.len() / 2])
+        .sum())
 }
 
-pub fn solve_p2(input: &str) -> u32 {
-    let (map, update_list) = parse(input).unwrap().1;
+/// Fails on malformed input or when the rules for an update form a cycle
+pub fn solve_p2(input: &str) -> anyhow::Result<u32> {
+    let (rules, update_list) = parse_all(input, parse)?;
 
-    update_list
+    Ok(update_list
         .into_iter()
-        .filter_map(|mut updates| {
-            let mut well_ordered = true;
-            let mut disallowed = HashSet::new();
-
-            for update in updates.iter() {
-                if disallowed.contains(update) {
-                    well_ordered = false;
-                    break;
-                }
-                if let Some(xs) = map.get(update) {
-                    for x in xs {
-                        disallowed.insert(x);
-                    }
-                }
-            }
-
-            if !well_ordered {
-                updates.sort_by(|a, b| {
-                    let a_comes_before = map.get(a).cloned().unwrap_or_default();
-                    let b_comes_before = map.get(b).cloned().unwrap_or_default();
-
-                    if a_comes_before.contains(b) {
-                        std::cmp::Ordering::Less
-                    } else if b_comes_before.contains(a) {
-                        std::cmp::Ordering::Greater
-                    } else {
-                        std::cmp::Ordering::Equal
-                    }
-                });
-                Some(updates[updates.len() / 2])
-            } else {
-                None
-            }
+        .filter(|updates| !rules.is_ordered(updates))
+        .map(|updates| {
+            let sorted = rules.sort(&updates)?;
+            Ok(sorted[sorted.len() / 2])
         })
-        .sum()
+        .sum::<Result<u32, Cycle<u32>>>()?)
 }
 
 #[cfg(test)]
@@ -122,26 +66,42 @@ mod day05_tests {
 
     const SAMPLE: &str = include_str!("../../inputs/day05/sample.txt");
 
+    #[test]
+    fn malformed_input_reports_position() {
+        let e = solve_p1("47|53\n97-13\n\n75,47,61\n").unwrap_err();
+        assert_eq!((e.line, e.column), (2, 3));
+        assert_eq!(e.expected, "page ordering rule");
+        let e = solve_p2("47|53\n\n75,47,61\n75;29\n").unwrap_err();
+        let e = e.downcast_ref::<ParseError>().unwrap();
+        assert_eq!((e.line, e.column), (4, 3));
+    }
+
+    #[test]
+    fn cyclic_rules_are_an_error() {
+        let e = solve_p2("1|2\n2|1\n\n1,2\n").unwrap_err();
+        assert!(e.downcast_ref::<Cycle<u32>>().is_some());
+    }
+
     #[test]
     fn p1_sample() {
-        assert_eq!(solve_p1(SAMPLE), 143)
+        assert_eq!(solve_p1(SAMPLE), Ok(143))
     }
 
     #[test]
     fn p1_input() {
         let input = &read_input(DAY);
-        assert_eq!(solve_p1(input), 6951)
+        assert_eq!(solve_p1(input), Ok(6951))
     }
 
     #[test]
     fn p2_sample() {
-        assert_eq!(solve_p2(SAMPLE), 123)
+        assert_eq!(solve_p2(SAMPLE).unwrap(), 123)
     }
 
     #[test]
     fn p2_input() {
         let input = &read_input(DAY);
-        assert_eq!(solve_p2(input), 4121)
+        assert_eq!(solve_p2(input).unwrap(), 4121)
     }
 }
 
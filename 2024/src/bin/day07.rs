@@ -1,126 +1,68 @@
 #![feature(test)]
 extern crate test;
-use aoc_2024::*;
-use itertools::Itertools;
-use nom::{
-    bytes::complete as bytes, character::complete as character, multi::*, sequence::*, IResult,
-    Parser,
-};
+use aoc_2024::{operators::*, parsers::*, *};
+use nom::{character::complete as character, error::VerboseError, IResult, Parser};
 use tracing::info;
 
 const DAY: u8 = 7;
-const OPERATIONS: [char; 2] = ['+', '*'];
-const OPERATIONS_2: [char; 3] = ['+', '*', '|'];
 
 #[tracing::instrument]
-fn main() {
+fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt::init();
     let input = &read_input(DAY);
     println!(
         "Day {:0>2}: Part 1 answer = {}, Part 2 answer = {}",
         DAY,
-        solve_p1(input),
-        solve_p2(input)
+        solve_p1(input)?,
+        solve_p2(input)?
     );
+    Ok(())
 }
 
-#[derive(Debug)]
-struct RopeBridge {
-    total: i64,
-    operands: Vec<i64>,
+fn parse_rope_bridge(s: &str) -> IResult<&str, Equation, VerboseError<&str>> {
+    key_value(character::i64, signed_list)
+        .map(|(total, operands)| Equation::new(total, operands))
+        .parse(s)
 }
-
-fn parse_rope_bridge(s: &str) -> IResult<&str, RopeBridge> {
-    separated_pair(
-        character::i64,
-        bytes::tag(": "),
-        separated_list1(bytes::tag(" "), character::i64),
-    )
-    .map(|pair| RopeBridge {
-        total: pair.0,
-        operands: pair.1,
-    })
-    .parse(s)
-}
-fn parse(s: &str) -> IResult<&str, Vec<RopeBridge>> {
-    separated_list1(character::line_ending, parse_rope_bridge)(s)
+fn parse(s: &str) -> IResult<&str, Vec<Equation>, VerboseError<&str>> {
+    lines_of(parse_rope_bridge)(s)
 }
 
 #[tracing::instrument(ret)]
-fn test_bridge(bridge: &RopeBridge) -> bool {
-    let n_operators = bridge.operands.len() - 1;
-    tracing::info!(n_operators);
-    (0..n_operators)
-        .map(|_| OPERATIONS)
-        .multi_cartesian_product()
-        .any(|mut operators| {
-            info!("{:?}", operators);
-            bridge
-                .operands
-                .iter()
-                .copied()
-                .reduce(|acc, right| {
-                    match operators
-                        .pop()
-                        .expect("Always have correct amount of operators")
-                    {
-                        '*' => acc * right,
-                        '+' => acc + right,
-                        _ => unreachable!("No other operators supported"),
-                    }
-                })
-                .expect("Should always have at least 1 operand")
-                == bridge.total
-        })
+fn calibrate(bridge: &Equation, operators: &[Operator]) -> bool {
+    match bridge.solve(operators) {
+        Some(ops) => {
+            info!("{}", bridge.expression(&ops));
+            true
+        }
+        None => false,
+    }
+}
+
+fn test_bridge(bridge: &Equation) -> bool {
+    calibrate(bridge, &Operator::ARITHMETIC)
 }
 
-pub fn solve_p1(input: &str) -> i64 {
-    let bridges = parse(input).unwrap().1;
-    bridges
+pub fn solve_p1(input: &str) -> Result<i64, ParseError> {
+    let bridges = parse_all(input, parse)?;
+    Ok(bridges
         .into_iter()
         .filter(test_bridge)
-        .map(|b| b.total)
-        .sum()
+        .map(|b| b.target)
+        .sum())
 }
 
-#[tracing::instrument(ret)]
-fn test_bridge_2(bridge: &RopeBridge) -> bool {
-    let n_operators = bridge.operands.len() - 1;
-    tracing::info!(n_operators);
-    (0..n_operators)
-        .map(|_| OPERATIONS_2)
-        .multi_cartesian_product()
-        .any(|mut operators| {
-            info!("{:?}", operators);
-            bridge
-                .operands
-                .iter()
-                .copied()
-                .reduce(|acc, right| {
-                    match operators
-                        .pop()
-                        .expect("Always have correct amount of operators")
-                    {
-                        '*' => acc * right,
-                        '+' => acc + right,
-                        '|' => format!("{}{}", acc, right)
-                            .parse::<i64>()
-                            .expect("two +ints should always concat to a +int"),
-                        _ => unreachable!("No other operators supported"),
-                    }
-                })
-                .expect("Should always have at least 1 operand")
-                == bridge.total
-        })
+fn test_bridge_2(bridge: &Equation) -> bool {
+    calibrate(bridge, &Operator::ALL)
 }
 
-pub fn solve_p2(input: &str) -> i64 {
-    let bridges = parse(input).unwrap().1;
-    bridges
+pub fn solve_p2(input: &str) -> Result<i64, ParseError> {
+    let bridges = parse_all(input, parse)?;
+    Ok(bridges
         .into_iter()
         .filter(test_bridge_2)
-        .map(|b| b.total)
-        .sum()
+        .map(|b| b.target)
+        .sum())
 }
 
 #[cfg(test)]
@@ -151,27 +93,32 @@ mod day07_tests {
         assert!(test_bridge(&b))
     }
 
+    #[test]
+    fn malformed_input_reports_position() {
+        let e = solve_p1("190: 10 19\n3267 81 40 27\n").unwrap_err();
+        assert_eq!((e.line, e.column), (2, 5));
+    }
+
     #[test]
     fn p1_sample() {
-        assert_eq!(solve_p1(SAMPLE), 3749)
+        assert_eq!(solve_p1(SAMPLE), Ok(3749))
     }
 
     #[test]
     fn p1_input() {
         let input = &read_input(DAY);
-        assert_eq!(solve_p1(input), 12940396350192)
+        assert_eq!(solve_p1(input), Ok(12940396350192))
     }
 
     #[test]
     fn p2_sample() {
-        assert_eq!(solve_p2(SAMPLE), 11387)
+        assert_eq!(solve_p2(SAMPLE), Ok(11387))
     }
 
     #[test]
-    #[ignore]
     fn p2_input() {
         let input = &read_input(DAY);
-        assert_eq!(solve_p2(input), 106016735664498)
+        assert_eq!(solve_p2(input), Ok(106016735664498))
     }
 }
 
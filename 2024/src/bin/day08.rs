@@ -1,11 +1,9 @@
 #![feature(test)]
 extern crate test;
-use aoc_2024::*;
+use aoc_2024::{lattice::*, tokenizer::TokenGrid, *};
 use glam::IVec2;
 use itertools::Itertools;
-use nom::{bytes::complete as bytes, character::complete as character, multi::*, IResult};
-use nom_locate::LocatedSpan;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use tracing::info;
 
 const DAY: u8 = 8;
@@ -22,79 +20,67 @@ fn main() {
     );
 }
 
-type Span<'a> = LocatedSpan<&'a str>;
 type NodeMap = HashMap<char, Vec<IVec2>>;
 
-fn parse_next_char(s: Span) -> IResult<Span, (char, IVec2)> {
-    let (s, _) = bytes::take_while(|c| c == '.' || c == '\n')(s)?;
-    let (rest, c) = character::anychar(s)?;
-    let p = IVec2::new(s.location_line() as i32 - 1, s.get_column() as i32 - 1);
-    Ok((rest, (c, p)))
-}
-
-fn parse(s: Span) -> IResult<Span, NodeMap> {
-    fold_many1(parse_next_char, NodeMap::new, |mut acc, (c, p)| {
-        acc.entry(c).or_default().push(p);
-        acc
-    })(s)
+fn parse(s: &str) -> NodeMap {
+    TokenGrid::parse_cells(s, '.')
+        .expect("Antenna map should tokenize")
+        .tokens()
+        .iter()
+        .fold(NodeMap::new(), |mut acc, t| {
+            acc.entry(t.char()).or_default().push(t.pos);
+            acc
+        })
 }
 
-#[tracing::instrument(skip(input))]
-pub fn solve_p1(input: &str) -> usize {
-    let node_map = parse(input.into()).unwrap().1;
-    let width = input.lines().next().unwrap().len() as i32 - 1;
-    let height = input.lines().count() as i32 - 1;
-    info!(width, height);
-
+/// The antinodes from every pair of antennas with the same frequency
+fn antinodes(node_map: NodeMap, pair: impl Fn(IVec2, IVec2) -> Vec<IVec2>) -> HashSet<IVec2> {
     node_map
         .into_values()
         .flat_map(|nodes| {
             nodes
                 .into_iter()
-                .combinations(2)
-                .flat_map(|xs| {
-                    let d = xs[1] - xs[0];
-                    [xs[0] - d, xs[1] + d]
-                })
-                .filter(|an| (0..=width).contains(&an.x) && (0..=height).contains(&an.y))
+                .tuple_combinations()
+                .flat_map(|(a, b)| pair(a, b))
+                .collect::<Vec<_>>()
         })
-        .unique()
-        .count()
+        .collect()
 }
 
-pub fn solve_p2(input: &str) -> usize {
-    let node_map = parse(input.into()).unwrap().1;
-    let width = input.lines().next().unwrap().len() as i32 - 1;
-    let height = input.lines().count() as i32 - 1;
-    info!(width, height);
+fn picture(input: &str, bounds: Bounds, antinodes: &HashSet<IVec2>) -> String {
+    let labels = parse(input)
+        .into_iter()
+        .flat_map(|(c, nodes)| nodes.into_iter().map(move |p| (p, c)))
+        .collect();
+    render(bounds, &labels, antinodes)
+}
 
-    node_map
-        .into_values()
-        .flat_map(|nodes| {
-            nodes
-                .into_iter()
-                .combinations(2)
-                .flat_map(|xs| {
-                    let d = xs[0] - xs[1];
-                    let mut ans = vec![];
-
-                    let mut a = xs[0];
-                    while (0..=width).contains(&a.x) && (0..=height).contains(&a.y) {
-                        a -= d;
-                        ans.push(a);
-                    }
-
-                    let mut b = xs[1];
-                    while (0..=width).contains(&b.x) && (0..=height).contains(&b.y) {
-                        b += d;
-                        ans.push(b);
-                    }
-                    ans
-                })
-                .filter(|an| (0..=width).contains(&an.x) && (0..=height).contains(&an.y))
-        })
-        .unique()
-        .count()
+#[tracing::instrument(skip(input))]
+pub fn solve_p1(input: &str) -> usize {
+    let bounds = Bounds::of_grid(input);
+    info!(?bounds);
+
+    let antinodes = antinodes(parse(input), |a, b| {
+        [at_ratio(a, b, -1, 1), at_ratio(a, b, 2, 1)]
+            .into_iter()
+            .flatten()
+            .filter(|p| bounds.contains(*p))
+            .collect()
+    });
+    tracing::debug!("\n{}", picture(input, bounds, &antinodes));
+    antinodes.len()
+}
+
+#[tracing::instrument(skip(input))]
+pub fn solve_p2(input: &str) -> usize {
+    let bounds = Bounds::of_grid(input);
+    info!(?bounds);
+
+    let antinodes = antinodes(parse(input), |a, b| {
+        Line::through(a, b).map_or(vec![], |line| line.points(bounds))
+    });
+    tracing::debug!("\n{}", picture(input, bounds, &antinodes));
+    antinodes.len()
 }
 
 #[cfg(test)]
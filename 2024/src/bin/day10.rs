@@ -1,14 +1,8 @@
 #![feature(test)]
 extern crate test;
-use aoc_2024::*;
-
-use tracing::info;
-
+use aoc_2024::{dag::*, tokenizer::TokenGrid, *};
 use glam::{ivec2, IVec2};
-use nom::{bytes::complete as bytes, character::complete as character, multi::*, IResult, Parser};
-use nom_locate::LocatedSpan;
-use nom_supreme::ParserExt;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
 const DAY: u8 = 10;
 
@@ -24,135 +18,52 @@ fn main() {
     );
 }
 
-type Span<'a> = LocatedSpan<&'a str>;
-
 type HeightMap = HashMap<IVec2, i32>;
 
-fn height_with_location(s: Span) -> IResult<Span, (IVec2, i32)> {
-    let p = ivec2(s.get_column() as i32 - 1, s.location_line() as i32 - 1);
-
-    let (rest, number_span) = bytes::take(1usize)(s)?;
-
-    let (_, x) = character::i32(number_span)?;
-    Ok((rest, (p, x)))
-}
-fn parse(s: Span) -> IResult<Span, HeightMap> {
-    let (rest, xs) = separated_list1(character::line_ending, many1(height_with_location))(s)?;
-    let hm = xs.into_iter().flatten().collect();
-    Ok((rest, hm))
-}
-
-#[tracing::instrument(skip(input))]
-pub fn solve_p1(input: &str) -> usize {
-    let hm = parse(input.into()).unwrap().1;
-    let width = hm.keys().map(|k| k.x).max().unwrap();
-    let height = hm.keys().map(|k| k.y).max().unwrap();
-    let bounds = ivec2(width, height);
-
-    hm.iter()
-        .filter_map(|node| {
-            if *node.1 == 0 {
-                Some(score_node_by_peaks_reached(
-                    *node.0,
-                    &hm,
-                    0,
-                    &bounds,
-                    &mut HashMap::new(),
-                ))
-            } else {
-                None
-            }
-        })
-        .sum()
+fn parse(s: &str) -> HeightMap {
+    TokenGrid::parse_cells(s, '.')
+        .expect("Topographic map should tokenize")
+        .tokens()
+        .iter()
+        .filter_map(|t| Some((t.pos, t.value::<i32>()?)))
+        .collect()
 }
 
 const OFFSETS: [IVec2; 4] = [ivec2(0, 1), ivec2(0, -1), ivec2(1, 0), ivec2(-1, 0)];
-fn score_node_by_peaks_reached(
-    node: IVec2,
-    heightmap: &HeightMap,
-    score: usize,
-    bounds: &IVec2,
-    memo: &mut HashMap<IVec2, usize>,
-) -> usize {
-    if let Some(s) = memo.get(&node) {
-        return *s;
-    }
-    if heightmap[&node] == 9 {
-        memo.insert(node, 1);
-        return 1;
-    }
 
-    OFFSETS
-        .into_iter()
-        .filter_map(|o| {
-            let adj_node = node + o;
-            if !(0..=bounds.x).contains(&adj_node.x)
-                || !(0..=bounds.y).contains(&adj_node.y)
-                || heightmap[&adj_node] - heightmap[&node] != 1
-            {
-                return None;
-            }
-
-            let s = score_node_by_peaks_reached(node + o, heightmap, score, bounds, memo);
-            memo.insert(node + o, score);
-            Some(s)
-        })
-        .sum()
+/// Trails only ever go up by one, so ordering by height is topological
+fn trails(heightmap: &HeightMap) -> Dag<IVec2> {
+    Dag::ranked(
+        heightmap.keys().copied(),
+        |node| heightmap[&node],
+        |node| {
+            OFFSETS
+                .into_iter()
+                .map(|o| node + o)
+                .filter(|adj| heightmap.get(adj) == Some(&(heightmap[&node] + 1)))
+                .collect()
+        },
+    )
 }
 
-fn score_node_by_forks(
-    node: IVec2,
-    heightmap: &HeightMap,
-    bounds: &IVec2,
-    memo: &mut HashMap<IVec2, usize>,
-) -> usize {
-    if let Some(s) = memo.get(&node) {
-        return *s;
-    }
-    if heightmap[&node] == 9 {
-        memo.insert(node, 1);
-        return 1;
-    }
+/// The path counts to a peak, along with every trailhead
+fn trailheads(input: &str) -> (PathCounts<IVec2>, Vec<IVec2>) {
+    let hm = parse(input);
+    let counts = trails(&hm).count_paths(|node| hm[&node] == 9);
+    let heads = hm.iter().filter(|n| *n.1 == 0).map(|n| *n.0).collect();
+    (counts, heads)
+}
 
-    OFFSETS
-        .into_iter()
-        .filter_map(|o| {
-            let adj_node = node + o;
-            if !(0..=bounds.x).contains(&adj_node.x)
-                || !(0..=bounds.y).contains(&adj_node.y)
-                || heightmap[&adj_node] - heightmap[&node] != 1
-            {
-                return None;
-            }
-
-            let s = score_node_by_forks(node + o, heightmap, bounds, memo);
-            memo.insert(node + o, s);
-            Some(s)
-        })
-        .sum()
+#[tracing::instrument(skip(input))]
+pub fn solve_p1(input: &str) -> usize {
+    let (counts, heads) = trailheads(input);
+    heads.into_iter().map(|h| counts.reachable(h)).sum()
 }
 
 #[tracing::instrument(skip(input))]
-pub fn solve_p2(input: &str) -> usize {
-    let hm = parse(input.into()).unwrap().1;
-    let width = hm.keys().map(|k| k.x).max().unwrap();
-    let height = hm.keys().map(|k| k.y).max().unwrap();
-    let bounds = ivec2(width, height);
-
-    hm.iter()
-        .filter_map(|node| {
-            if *node.1 == 0 {
-                Some(score_node_by_forks(
-                    *node.0,
-                    &hm,
-                    &bounds,
-                    &mut HashMap::new(),
-                ))
-            } else {
-                None
-            }
-        })
-        .sum()
+pub fn solve_p2(input: &str) -> u64 {
+    let (counts, heads) = trailheads(input);
+    heads.into_iter().map(|h| counts.paths(h)).sum()
 }
 
 #[cfg(test)]
@@ -0,0 +1,167 @@
+//! Counting paths through a directed acyclic graph, like the hiking trails in day 10.
+//!
+//! Nodes are visited once, in reverse topological order, so every node's successors have already
+//! been counted when it's reached. Each node ends up knowing both which sinks it can reach and
+//! how many distinct paths lead to them, so scoring every source is a lookup.
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+#[derive(Debug, Clone)]
+pub struct Dag<N> {
+    /// Every node, each before all of its successors
+    order: Vec<N>,
+    successors: HashMap<N, Vec<N>>,
+}
+
+impl<N: Copy + Eq + Hash> Dag<N> {
+    /// `order` has to list every node before its successors
+    pub fn new(order: Vec<N>, successors: impl Fn(N) -> Vec<N>) -> Self {
+        let successors = order.iter().map(|&n| (n, successors(n))).collect();
+        Self { order, successors }
+    }
+
+    /// A graph where every edge goes to a node with a larger `rank`, like a height, which puts
+    /// the nodes in topological order
+    pub fn ranked<K: Ord>(
+        nodes: impl IntoIterator<Item = N>,
+        rank: impl Fn(N) -> K,
+        successors: impl Fn(N) -> Vec<N>,
+    ) -> Self {
+        let mut order = nodes.into_iter().collect::<Vec<_>>();
+        order.sort_by_key(|&n| rank(n));
+        Self::new(order, successors)
+    }
+
+    pub fn successors(&self, node: N) -> &[N] {
+        self.successors.get(&node).map_or(&[], Vec::as_slice)
+    }
+
+    /// Count the paths from every node to the nodes where `is_sink` holds. Paths stop at the
+    /// first sink they reach.
+    pub fn count_paths(&self, is_sink: impl Fn(N) -> bool) -> PathCounts<N> {
+        let mut sinks = HashMap::<N, HashSet<N>>::new();
+        let mut paths = HashMap::<N, u64>::new();
+        for &node in self.order.iter().rev() {
+            if is_sink(node) {
+                sinks.insert(node, HashSet::from([node]));
+                paths.insert(node, 1);
+                continue;
+            }
+            let mut reached = HashSet::new();
+            let mut count = 0;
+            for next in self.successors(node) {
+                reached.extend(sinks.get(next).into_iter().flatten());
+                count += paths.get(next).copied().unwrap_or(0);
+            }
+            sinks.insert(node, reached);
+            paths.insert(node, count);
+        }
+        PathCounts { sinks, paths }
+    }
+
+    /// Every path from `source` to a sink, following only nodes that lead to one
+    pub fn paths(&self, source: N, counts: &PathCounts<N>) -> Vec<Vec<N>> {
+        let mut found = vec![];
+        let mut path = vec![source];
+        self.extend_paths(&mut path, counts, &mut found);
+        found
+    }
+
+    fn extend_paths(&self, path: &mut Vec<N>, counts: &PathCounts<N>, found: &mut Vec<Vec<N>>) {
+        let node = *path.last().expect("Paths are never empty");
+        // only a sink can reach itself
+        if counts.sinks.get(&node).is_some_and(|s| s.contains(&node)) {
+            found.push(path.clone());
+            return;
+        }
+        for &next in self.successors(node) {
+            if counts.paths(next) > 0 {
+                path.push(next);
+                self.extend_paths(path, counts, found);
+                path.pop();
+            }
+        }
+    }
+}
+
+/// See [`Dag::count_paths`]
+#[derive(Debug, Clone)]
+pub struct PathCounts<N> {
+    sinks: HashMap<N, HashSet<N>>,
+    paths: HashMap<N, u64>,
+}
+
+impl<N: Copy + Eq + Hash> PathCounts<N> {
+    /// The sinks that can be reached from `node`
+    pub fn sinks(&self, node: N) -> HashSet<N> {
+        self.sinks.get(&node).cloned().unwrap_or_default()
+    }
+
+    /// How many different sinks can be reached from `node`
+    pub fn reachable(&self, node: N) -> usize {
+        self.sinks.get(&node).map_or(0, HashSet::len)
+    }
+
+    /// How many distinct paths lead from `node` to a sink
+    pub fn paths(&self, node: N) -> u64 {
+        self.paths.get(&node).copied().unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 0 -> 1 -> 3, 0 -> 2 -> 3, 2 -> 4, 5 -> 4
+    fn diamond() -> Dag<u32> {
+        Dag::new(vec![0, 5, 1, 2, 3, 4], |n| match n {
+            0 => vec![1, 2],
+            1 => vec![3],
+            2 => vec![3, 4],
+            5 => vec![4],
+            _ => vec![],
+        })
+    }
+
+    #[test]
+    fn counting() {
+        let dag = diamond();
+        let counts = dag.count_paths(|n| n == 3 || n == 4);
+        assert_eq!(counts.reachable(0), 2);
+        assert_eq!(counts.paths(0), 3);
+        assert_eq!(counts.paths(2), 2);
+        assert_eq!(counts.sinks(5), HashSet::from([4]));
+
+        // 4 isn't a sink any more, so paths ending there don't count
+        let counts = dag.count_paths(|n| n == 3);
+        assert_eq!(counts.paths(0), 2);
+        assert_eq!(counts.reachable(5), 0);
+        assert_eq!(counts.paths(9), 0);
+    }
+
+    #[test]
+    fn enumerating() {
+        let dag = diamond();
+        let counts = dag.count_paths(|n| n == 3 || n == 4);
+        let paths = dag.paths(0, &counts);
+        assert_eq!(paths, [vec![0, 1, 3], vec![0, 2, 3], vec![0, 2, 4]]);
+        assert_eq!(paths.len() as u64, counts.paths(0));
+        assert_eq!(
+            dag.paths(5, &dag.count_paths(|n| n == 3)),
+            Vec::<Vec<u32>>::new()
+        );
+    }
+
+    #[test]
+    fn ranked_order() {
+        // edges go from each number to the next, listed in any order
+        let dag = Dag::ranked(
+            [3, 1, 2, 0],
+            |n| n,
+            |n| if n < 3 { vec![n + 1] } else { vec![] },
+        );
+        let counts = dag.count_paths(|n| n == 3);
+        assert_eq!(counts.paths(0), 1);
+        assert_eq!(dag.paths(1, &counts), [vec![1, 2, 3]]);
+    }
+}
@@ -0,0 +1,240 @@
+//! A disk of fixed size blocks holding files and free space, compacted like the amphipod's disk
+//! in day 9.
+//!
+//! Free spans are indexed by their length, each length keeping a min-heap of where its spans
+//! start. Finding the leftmost span that fits a file only has to peek at one heap per length
+//! instead of scanning the disk, so moving every file is `O(n log n)`.
+use anyhow::{Context, Result};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt::Display;
+
+/// A run of blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Extent {
+    pub start: usize,
+    pub len: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Disk {
+    /// The file id in every block
+    blocks: Vec<Option<usize>>,
+    /// Where each file is, by id. Empty once the files have been fragmented.
+    files: Vec<Extent>,
+    /// The start of every free span, indexed by the span's length
+    free: Vec<BinaryHeap<Reverse<usize>>>,
+}
+
+impl Disk {
+    /// Parse a disk map, the digits alternating between file and free space lengths
+    pub fn from_map(map: &str) -> Result<Self> {
+        let mut blocks = vec![];
+        let mut files = vec![];
+        let mut free = vec![];
+        for (i, c) in map.trim().chars().enumerate() {
+            let len = c
+                .to_digit(10)
+                .with_context(|| format!("'{}' at {} isn't a digit", c, i))?
+                as usize;
+            let extent = Extent {
+                start: blocks.len(),
+                len,
+            };
+            if i % 2 == 0 {
+                blocks.extend(std::iter::repeat_n(Some(files.len()), len));
+                files.push(extent);
+            } else {
+                blocks.extend(std::iter::repeat_n(None, len));
+                if len > 0 {
+                    free.push(extent);
+                }
+            }
+        }
+        let mut disk = Self {
+            blocks,
+            files,
+            free: vec![],
+        };
+        for extent in free {
+            disk.release(extent);
+        }
+        Ok(disk)
+    }
+
+    pub fn blocks(&self) -> &[Option<usize>] {
+        &self.blocks
+    }
+
+    pub fn files(&self) -> &[Extent] {
+        &self.files
+    }
+
+    fn release(&mut self, extent: Extent) {
+        if self.free.len() <= extent.len {
+            self.free.resize_with(extent.len + 1, BinaryHeap::new);
+        }
+        self.free[extent.len].push(Reverse(extent.start));
+    }
+
+    /// The leftmost free span of at least `len` blocks that starts before `before`
+    pub fn free_span(&self, len: usize, before: usize) -> Option<Extent> {
+        (len..self.free.len())
+            .filter_map(|l| {
+                self.free[l]
+                    .peek()
+                    .map(|&Reverse(start)| Extent { start, len: l })
+            })
+            .filter(|e| e.start < before)
+            .min_by_key(|e| e.start)
+    }
+
+    /// Move a whole file into the leftmost free span to its left that fits it, returning where
+    /// it ended up. Space a file leaves behind isn't reused, which only holds up because
+    /// [`Disk::compact_with`] moves files left in descending id order.
+    fn move_file(&mut self, id: usize) -> Option<Extent> {
+        let file = *self.files.get(id)?;
+        let span = self.free_span(file.len, file.start)?;
+        self.free[span.len].pop();
+        if span.len > file.len {
+            self.release(Extent {
+                start: span.start + file.len,
+                len: span.len - file.len,
+            });
+        }
+        let moved = Extent {
+            start: span.start,
+            len: file.len,
+        };
+        self.blocks[moved.start..moved.start + moved.len].fill(Some(id));
+        self.blocks[file.start..file.start + file.len].fill(None);
+        self.files[id] = moved;
+        Some(moved)
+    }
+
+    /// Try to move every file once, highest id first
+    pub fn compact(&mut self) {
+        self.compact_with(|_| {})
+    }
+
+    /// [`Disk::compact`], handing `hook` the disk after every file that moved
+    pub fn compact_with(&mut self, mut hook: impl FnMut(&Disk)) {
+        for id in (0..self.files.len()).rev() {
+            if self.move_file(id).is_some() {
+                hook(self);
+            }
+        }
+    }
+
+    /// Move blocks one at a time from the end of the disk into the leftmost free block, until
+    /// there are no gaps. Files get split up, so they aren't tracked after this.
+    pub fn fragment(&mut self) {
+        self.fragment_with(|_| {})
+    }
+
+    /// [`Disk::fragment`], handing `hook` the disk after every block that moved
+    pub fn fragment_with(&mut self, mut hook: impl FnMut(&Disk)) {
+        self.files.clear();
+        self.free.clear();
+        let (mut left, mut right) = (0, self.blocks.len().saturating_sub(1));
+        while left < right {
+            if self.blocks[left].is_some() {
+                left += 1;
+            } else if self.blocks[right].is_none() {
+                right -= 1;
+            } else {
+                self.blocks.swap(left, right);
+                hook(self);
+            }
+        }
+    }
+
+    /// Sum of every block's position times the id of the file in it
+    pub fn checksum(&self) -> usize {
+        self.blocks
+            .iter()
+            .enumerate()
+            .filter_map(|(i, id)| id.map(|id| i * id))
+            .sum()
+    }
+}
+
+impl Display for Disk {
+    /// One character per block like the puzzle, `.` for free space and the last digit of the
+    /// file id otherwise
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let layout = self
+            .blocks
+            .iter()
+            .map(|b| match b {
+                Some(id) => char::from_digit((id % 10) as u32, 10).unwrap(),
+                None => '.',
+            })
+            .collect::<String>();
+        write!(f, "{}", layout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: &str = "2333133121414131402";
+
+    #[test]
+    fn layout() {
+        let disk = Disk::from_map(MAP).unwrap();
+        assert_eq!(
+            disk.to_string(),
+            "00...111...2...333.44.5555.6666.777.888899"
+        );
+        assert_eq!(disk.files()[9], Extent { start: 40, len: 2 });
+        assert_eq!(disk.free_span(3, 40), Some(Extent { start: 2, len: 3 }));
+        assert_eq!(disk.free_span(4, 40), None);
+        assert!(Disk::from_map("12x").is_err());
+    }
+
+    #[test]
+    fn fragmenting() {
+        let mut disk = Disk::from_map("12345").unwrap();
+        let mut frames = vec![];
+        disk.fragment_with(|d| frames.push(d.to_string()));
+        assert_eq!(
+            frames,
+            [
+                "02.111....2222.",
+                "022111....222..",
+                "0221112...22...",
+                "02211122..2....",
+                "022111222......",
+            ]
+        );
+        assert_eq!(
+            Disk::from_map(MAP)
+                .map(|mut d| {
+                    d.fragment();
+                    d.checksum()
+                })
+                .unwrap(),
+            1928
+        );
+    }
+
+    #[test]
+    fn compacting_whole_files() {
+        let mut disk = Disk::from_map(MAP).unwrap();
+        let mut frames = vec![];
+        disk.compact_with(|d| frames.push(d.to_string()));
+        assert_eq!(
+            frames,
+            [
+                "0099.111...2...333.44.5555.6666.777.8888..",
+                "0099.1117772...333.44.5555.6666.....8888..",
+                "0099.111777244.333....5555.6666.....8888..",
+                "00992111777.44.333....5555.6666.....8888..",
+            ]
+        );
+        assert_eq!(disk.files()[2], Extent { start: 4, len: 1 });
+        assert_eq!(disk.checksum(), 2858);
+    }
+}
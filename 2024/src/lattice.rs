@@ -0,0 +1,176 @@
+//! Lines through points on an integer grid, like the antennas and antinodes in day 8.
+//!
+//! A [`Line`] through two grid points steps by their difference divided by its gcd, so it hits
+//! every grid point in between as well as those beyond. Points a given ratio of the way from one
+//! point to another come from [`at_ratio`].
+use glam::IVec2;
+use std::collections::{HashMap, HashSet};
+
+pub fn gcd(a: i32, b: i32) -> i32 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// An inclusive rectangle of grid points
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub min: IVec2,
+    pub max: IVec2,
+}
+
+impl Bounds {
+    pub fn new(min: IVec2, max: IVec2) -> Self {
+        Self { min, max }
+    }
+
+    /// The cells of a picture, one character per cell
+    pub fn of_grid(input: &str) -> Self {
+        let width = input.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+        let height = input.lines().count();
+        Self::new(IVec2::ZERO, IVec2::new(width as i32 - 1, height as i32 - 1))
+    }
+
+    pub fn contains(&self, p: IVec2) -> bool {
+        p.cmpge(self.min).all() && p.cmple(self.max).all()
+    }
+}
+
+/// The point `num / den` of the way from `a` to `b`, so 0 is `a`, 1 is `b`, -1 is as far
+/// behind `a` as `b` is in front and so on. `None` if it isn't on a grid point or `den` is 0.
+pub fn at_ratio(a: IVec2, b: IVec2, num: i32, den: i32) -> Option<IVec2> {
+    if den == 0 {
+        return None;
+    }
+    let scaled = (b - a) * num;
+    (scaled % den == IVec2::ZERO).then(|| a + scaled / den)
+}
+
+/// Every grid point in line with `a` and `b`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Line {
+    pub origin: IVec2,
+    /// The smallest step between grid points on the line
+    pub step: IVec2,
+}
+
+impl Line {
+    /// `None` if the points are the same, there are lines through it in every direction
+    pub fn through(a: IVec2, b: IVec2) -> Option<Self> {
+        let d = b - a;
+        if d == IVec2::ZERO {
+            return None;
+        }
+        Some(Self {
+            origin: a,
+            step: d / gcd(d.x, d.y),
+        })
+    }
+
+    pub fn contains(&self, p: IVec2) -> bool {
+        let d = p - self.origin;
+        d.perp_dot(self.step) == 0
+    }
+
+    /// The grid points on the line inside `bounds`, in order along the step
+    pub fn points(&self, bounds: Bounds) -> Vec<IVec2> {
+        // the multiples of the step that keep each coordinate in bounds, the line might only
+        // cross them far from the origin
+        let mut range = (i32::MIN, i32::MAX);
+        for (o, s, lo, hi) in [
+            (self.origin.x, self.step.x, bounds.min.x, bounds.max.x),
+            (self.origin.y, self.step.y, bounds.min.y, bounds.max.y),
+        ] {
+            let (o, s, lo, hi) = match s {
+                0 if o < lo || o > hi => return vec![],
+                0 => continue,
+                s if s < 0 => (-o, -s, -hi, -lo),
+                s => (o, s, lo, hi),
+            };
+            let first = -(o - lo).div_euclid(s);
+            let last = (hi - o).div_euclid(s);
+            range = (range.0.max(first), range.1.min(last));
+        }
+        (range.0..=range.1)
+            .map(|k| self.origin + self.step * k)
+            .collect()
+    }
+}
+
+/// A picture of the grid like the puzzle draws it. `marks` are drawn as `#` unless one of the
+/// `labels` is in the same place.
+pub fn render(bounds: Bounds, labels: &HashMap<IVec2, char>, marks: &HashSet<IVec2>) -> String {
+    let mut s = String::new();
+    for y in bounds.min.y..=bounds.max.y {
+        for x in bounds.min.x..=bounds.max.x {
+            let p = IVec2::new(x, y);
+            s.push(match labels.get(&p) {
+                Some(&c) => c,
+                None if marks.contains(&p) => '#',
+                None => '.',
+            });
+        }
+        s.push('\n');
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ratios() {
+        let (a, b) = (IVec2::new(4, 3), IVec2::new(5, 5));
+        assert_eq!(at_ratio(a, b, -1, 1), Some(IVec2::new(3, 1)));
+        assert_eq!(at_ratio(a, b, 2, 1), Some(IVec2::new(6, 7)));
+        assert_eq!(at_ratio(a, b, 1, 2), None);
+        assert_eq!(at_ratio(a, b, 1, 0), None);
+        let (a, b) = (IVec2::new(0, 0), IVec2::new(3, 6));
+        assert_eq!(at_ratio(a, b, 1, 3), Some(IVec2::new(1, 2)));
+        assert_eq!(at_ratio(a, b, -2, 3), Some(IVec2::new(-2, -4)));
+    }
+
+    #[test]
+    fn lines_step_by_the_gcd() {
+        let line = Line::through(IVec2::new(1, 1), IVec2::new(7, 4)).unwrap();
+        assert_eq!(line.step, IVec2::new(2, 1));
+        assert!(line.contains(IVec2::new(3, 2)));
+        assert!(!line.contains(IVec2::new(3, 3)));
+        assert_eq!(
+            line.points(Bounds::new(IVec2::ZERO, IVec2::new(6, 6))),
+            [IVec2::new(1, 1), IVec2::new(3, 2), IVec2::new(5, 3)]
+        );
+        assert_eq!(Line::through(IVec2::ONE, IVec2::ONE), None);
+    }
+
+    #[test]
+    fn lines_outside_the_origin() {
+        let bounds = Bounds::new(IVec2::ZERO, IVec2::new(3, 3));
+        let vertical = Line::through(IVec2::new(2, -10), IVec2::new(2, -9)).unwrap();
+        assert_eq!(vertical.points(bounds).len(), 4);
+        let outside = Line::through(IVec2::new(5, 0), IVec2::new(5, 1)).unwrap();
+        assert_eq!(outside.points(bounds), []);
+        let falling = Line::through(IVec2::new(10, -7), IVec2::new(9, -6)).unwrap();
+        assert_eq!(
+            falling.points(bounds),
+            [
+                IVec2::new(3, 0),
+                IVec2::new(2, 1),
+                IVec2::new(1, 2),
+                IVec2::new(0, 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn rendering() {
+        let bounds = Bounds::of_grid("...\n...\n");
+        assert_eq!(bounds.max, IVec2::new(2, 1));
+        let labels = HashMap::from([(IVec2::new(1, 0), 'a')]);
+        let marks = HashSet::from([IVec2::new(1, 0), IVec2::new(2, 1)]);
+        assert_eq!(render(bounds, &labels, &marks), ".a.\n..#\n");
+    }
+}
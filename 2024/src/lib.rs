@@ -1,3 +1,14 @@
+pub mod dag;
+pub mod disk;
+pub mod lattice;
+pub mod operators;
+pub mod ordering;
+pub mod parsers;
+pub mod patrol;
+pub mod scanner;
+pub mod tokenizer;
+pub mod word_search;
+
 use std::collections::HashSet;
 use std::fs;
 use std::io::read_to_string;
@@ -0,0 +1,214 @@
+//! Finding the operators that turn a list of numbers into a target, like the bridge calibrations
+//! in day 7.
+//!
+//! Expressions are evaluated strictly left to right. Rather than trying every combination of
+//! operators going forwards, [`Equation::solve`] works backwards from the target, undoing the
+//! last operation: a product has to divide evenly, a concatenation has to end in the right
+//! digits and a sum can't go negative. Most branches die straight away.
+//!
+//! Operands are expected to be non-negative, like the puzzle's.
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operator {
+    Add,
+    Mul,
+    /// The digits of the left side followed by those of the right, `12 || 345` is `12345`
+    Concat,
+}
+
+impl Operator {
+    pub const ARITHMETIC: [Operator; 2] = [Operator::Add, Operator::Mul];
+    pub const ALL: [Operator; 3] = [Operator::Add, Operator::Mul, Operator::Concat];
+
+    /// `None` on overflow
+    pub fn apply(self, a: i64, b: i64) -> Option<i64> {
+        match self {
+            Operator::Add => a.checked_add(b),
+            Operator::Mul => a.checked_mul(b),
+            Operator::Concat => a.checked_mul(digits_shift(b))?.checked_add(b),
+        }
+    }
+
+    /// The left side that gives `result` with `b` on the right, if there is one
+    pub fn undo(self, result: i64, b: i64) -> Option<i64> {
+        match self {
+            Operator::Add => (result >= b).then(|| result - b),
+            Operator::Mul => (b != 0 && result % b == 0).then(|| result / b),
+            Operator::Concat => {
+                let shift = digits_shift(b);
+                (result % shift == b).then(|| result / shift)
+            }
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Operator::Add => "+",
+            Operator::Mul => "*",
+            Operator::Concat => "||",
+        }
+    }
+}
+
+/// The power of ten that shifts a number left past all the digits of `b`
+fn digits_shift(b: i64) -> i64 {
+    let mut shift = 10;
+    while shift <= b {
+        shift *= 10;
+    }
+    shift
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Equation {
+    pub target: i64,
+    pub operands: Vec<i64>,
+}
+
+impl Equation {
+    pub fn new(target: i64, operands: Vec<i64>) -> Self {
+        Self { target, operands }
+    }
+
+    /// The result of putting `operators` between the operands, `None` if the wrong number of
+    /// operators is given or it overflows
+    pub fn evaluate(&self, operators: &[Operator]) -> Option<i64> {
+        let (&first, rest) = self.operands.split_first()?;
+        if rest.len() != operators.len() {
+            return None;
+        }
+        rest.iter()
+            .zip(operators)
+            .try_fold(first, |acc, (&b, op)| op.apply(acc, b))
+    }
+
+    /// Operators from `allowed` that make the operands reach the target, if any do
+    pub fn solve(&self, allowed: &[Operator]) -> Option<Vec<Operator>> {
+        if self.operands.is_empty() {
+            return None;
+        }
+        let mut operators = vec![];
+        self.search(
+            allowed,
+            self.target,
+            self.operands.len() - 1,
+            &mut operators,
+        )
+        .then(|| {
+            operators.reverse();
+            operators
+        })
+    }
+
+    /// Whether the operands up to `last` can make `target`, pushing the operators that do from
+    /// the right
+    fn search(
+        &self,
+        allowed: &[Operator],
+        target: i64,
+        last: usize,
+        operators: &mut Vec<Operator>,
+    ) -> bool {
+        if last == 0 {
+            return target == self.operands[0];
+        }
+        let b = self.operands[last];
+        for &op in allowed {
+            // anything times zero is zero, so the rest can be whatever
+            if op == Operator::Mul && b == 0 && target == 0 {
+                operators.push(op);
+                operators.extend(std::iter::repeat_n(op, last - 1));
+                return true;
+            }
+            let Some(rest) = op.undo(target, b) else {
+                continue;
+            };
+            operators.push(op);
+            if self.search(allowed, rest, last - 1, operators) {
+                return true;
+            }
+            operators.pop();
+        }
+        false
+    }
+
+    /// The equation written out with `operators`, like `3267 = 81 * 40 + 27`
+    pub fn expression(&self, operators: &[Operator]) -> String {
+        let mut s = format!("{} =", self.target);
+        for (i, n) in self.operands.iter().enumerate() {
+            if i > 0 {
+                s.push_str(&format!(" {}", operators[i - 1].symbol()));
+            }
+            s.push_str(&format!(" {}", n));
+        }
+        s
+    }
+}
+
+impl Display for Equation {
+    /// In the puzzle's format, `3267: 81 40 27`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:", self.target)?;
+        for n in &self.operands {
+            write!(f, " {}", n)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Operator::*;
+
+    #[test]
+    fn operators_undo() {
+        for (a, b) in [(12, 345), (5, 0), (0, 7), (10, 10)] {
+            for op in Operator::ALL {
+                // multiplying by zero can't be undone
+                if op == Mul && b == 0 {
+                    continue;
+                }
+                let result = op.apply(a, b).unwrap();
+                assert_eq!(op.undo(result, b), Some(a), "{} {} {}", a, op.symbol(), b);
+            }
+        }
+        assert_eq!(Concat.apply(12, 345), Some(12345));
+        assert_eq!(Concat.undo(12345, 45), Some(123));
+        assert_eq!(Concat.undo(12345, 44), None);
+        assert_eq!(Mul.undo(10, 3), None);
+        assert_eq!(Add.undo(3, 10), None);
+        assert_eq!(Mul.apply(i64::MAX, 2), None);
+    }
+
+    #[test]
+    fn witnesses() {
+        let eq = Equation::new(3267, vec![81, 40, 27]);
+        let ops = eq.solve(&Operator::ARITHMETIC).unwrap();
+        assert_eq!(eq.evaluate(&ops), Some(3267));
+        // `81 + 40 * 27` works too, but undoing a sum is tried first
+        assert_eq!(eq.expression(&ops), "3267 = 81 * 40 + 27");
+
+        let eq = Equation::new(7290, vec![6, 8, 6, 15]);
+        assert_eq!(eq.solve(&Operator::ARITHMETIC), None);
+        assert_eq!(eq.solve(&Operator::ALL), Some(vec![Mul, Concat, Mul]));
+        assert_eq!(eq.to_string(), "7290: 6 8 6 15");
+    }
+
+    #[test]
+    fn edge_cases() {
+        assert_eq!(Equation::new(5, vec![5]).solve(&[Add]), Some(vec![]));
+        assert_eq!(Equation::new(4, vec![5]).solve(&[Add]), None);
+        assert_eq!(Equation::new(4, vec![]).solve(&[Add]), None);
+        let zero = Equation::new(0, vec![3, 9, 0]);
+        let ops = zero.solve(&Operator::ALL).unwrap();
+        assert_eq!(zero.evaluate(&ops), Some(0));
+        // only the operators that are allowed get used
+        assert_eq!(
+            Equation::new(156, vec![15, 6]).solve(&[Concat]),
+            Some(vec![Concat])
+        );
+        assert_eq!(Equation::new(21, vec![15, 6]).solve(&[Concat, Mul]), None);
+    }
+}
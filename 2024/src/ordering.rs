@@ -0,0 +1,218 @@
+//! Partial orders given as "this comes before that" rules, like the page ordering rules in day 5.
+//!
+//! The rules only have to be consistent for the items that are actually being ordered, so
+//! [`Precedence::sort`] runs Kahn's algorithm on the rules between those items alone and reports
+//! a [`Cycle`] if they contradict each other.
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+
+/// Two items in a sequence that the rules say should be the other way around
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Violation<T> {
+    /// The item that comes first in the sequence, and its index
+    pub first: (usize, T),
+    /// The item that comes later, but has to be before `first`
+    pub second: (usize, T),
+}
+
+/// The items that couldn't be ordered, because they are in a cycle of rules or have to come after
+/// one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle<T>(pub Vec<T>);
+
+impl<T: Debug> Display for Cycle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The rules between {:?} form a cycle", self.0)
+    }
+}
+
+impl<T: Debug> std::error::Error for Cycle<T> {}
+
+#[derive(Debug, Clone)]
+pub struct Precedence<T> {
+    /// Every item that has to come after the key
+    successors: HashMap<T, HashSet<T>>,
+}
+
+impl<T> Default for Precedence<T> {
+    fn default() -> Self {
+        Self {
+            successors: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Copy + Eq + Hash> Precedence<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `before` has to come before `after` whenever both are present
+    pub fn add(&mut self, before: T, after: T) {
+        self.successors.entry(before).or_default().insert(after);
+    }
+
+    /// Whether a rule puts `a` directly before `b`, rules aren't chained
+    pub fn precedes(&self, a: T, b: T) -> bool {
+        self.successors.get(&a).is_some_and(|s| s.contains(&b))
+    }
+
+    /// The first pair of items that breaks a rule, by the position of the later item
+    pub fn violation(&self, sequence: &[T]) -> Option<Violation<T>> {
+        sequence.iter().enumerate().find_map(|(j, &b)| {
+            sequence[..j]
+                .iter()
+                .position(|&a| self.precedes(b, a))
+                .map(|i| Violation {
+                    first: (i, sequence[i]),
+                    second: (j, b),
+                })
+        })
+    }
+
+    pub fn is_ordered(&self, sequence: &[T]) -> bool {
+        self.violation(sequence).is_none()
+    }
+
+    /// `items` ordered so that every rule between them holds. When the rules allow more than one
+    /// item next, the one earliest in `items` goes first.
+    pub fn sort(&self, items: &[T]) -> Result<Vec<T>, Cycle<T>> {
+        let index = items
+            .iter()
+            .enumerate()
+            .map(|(i, &item)| (item, i))
+            .collect::<HashMap<_, _>>();
+        let successors = |i: usize| {
+            let item = items[i];
+            self.successors
+                .get(&item)
+                .into_iter()
+                .flatten()
+                .filter_map(|s| index.get(s).copied())
+        };
+
+        let mut predecessors = vec![0; items.len()];
+        for i in 0..items.len() {
+            for s in successors(i) {
+                predecessors[s] += 1;
+            }
+        }
+        let mut ready = (0..items.len())
+            .filter(|&i| predecessors[i] == 0)
+            .map(Reverse)
+            .collect::<BinaryHeap<_>>();
+        let mut sorted = Vec::with_capacity(items.len());
+        while let Some(Reverse(i)) = ready.pop() {
+            sorted.push(items[i]);
+            for s in successors(i) {
+                predecessors[s] -= 1;
+                if predecessors[s] == 0 {
+                    ready.push(Reverse(s));
+                }
+            }
+        }
+
+        if sorted.len() < items.len() {
+            return Err(Cycle(
+                (0..items.len())
+                    .filter(|&i| predecessors[i] > 0)
+                    .map(|i| items[i])
+                    .collect(),
+            ));
+        }
+        Ok(sorted)
+    }
+
+    /// Whether the rules can all hold at once, taking every item they mention
+    pub fn is_acyclic(&self) -> bool {
+        let items = self
+            .successors
+            .iter()
+            .flat_map(|(&a, after)| std::iter::once(a).chain(after.iter().copied()))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        self.sort(&items).is_ok()
+    }
+}
+
+impl<T: Copy + Eq + Hash> FromIterator<(T, T)> for Precedence<T> {
+    /// `(before, after)` pairs
+    fn from_iter<I: IntoIterator<Item = (T, T)>>(iter: I) -> Self {
+        let mut precedence = Self::new();
+        for (before, after) in iter {
+            precedence.add(before, after);
+        }
+        precedence
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> Precedence<u32> {
+        [
+            (47, 53),
+            (97, 13),
+            (97, 61),
+            (97, 47),
+            (75, 29),
+            (61, 13),
+            (75, 53),
+            (29, 13),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn violations() {
+        let rules = rules();
+        assert!(rules.is_ordered(&[75, 47, 61, 53, 29]));
+        assert_eq!(
+            rules.violation(&[75, 97, 47, 61, 53]),
+            None,
+            "97 and 75 have no rule between them"
+        );
+        assert_eq!(
+            rules.violation(&[61, 13, 29]),
+            Some(Violation {
+                first: (1, 13),
+                second: (2, 29)
+            })
+        );
+        assert_eq!(
+            rules.violation(&[13, 47, 97]),
+            Some(Violation {
+                first: (0, 13),
+                second: (2, 97)
+            })
+        );
+    }
+
+    #[test]
+    fn sorting_a_subset() {
+        let rules = rules();
+        assert_eq!(rules.sort(&[61, 13, 29]), Ok(vec![61, 29, 13]));
+        assert_eq!(
+            rules.sort(&[97, 13, 75, 29, 47]),
+            Ok(vec![97, 75, 29, 13, 47])
+        );
+        // unrelated items stay where they were
+        assert_eq!(rules.sort(&[5, 13, 3, 97]), Ok(vec![5, 3, 97, 13]));
+    }
+
+    #[test]
+    fn cycles() {
+        let mut rules = rules();
+        assert!(rules.is_acyclic());
+        rules.add(13, 97);
+        assert!(!rules.is_acyclic());
+        assert_eq!(rules.sort(&[29, 61, 13, 97]), Err(Cycle(vec![61, 13, 97])));
+        // none of the cycle is being sorted here
+        assert_eq!(rules.sort(&[47, 53, 75]), Ok(vec![47, 75, 53]));
+    }
+}
@@ -0,0 +1,237 @@
+//! Nom combinators for the input shapes that keep coming back every year.
+//!
+//! All of them are generic over the nom error type, and label what they are parsing with
+//! [`context`] so a verbose error (e.g. [`nom::error::VerboseError`]) says which part of the
+//! input shape broke.
+use glam::IVec2;
+use nom::{
+    bytes::complete as bytes,
+    character::complete as character,
+    error::{context, ContextError, ParseError as NomParseError},
+    multi::*,
+    sequence::*,
+    IResult, Parser,
+};
+use nom_locate::LocatedSpan;
+
+mod error;
+pub use error::{parse_all, parse_all_span, ParseError};
+
+pub type Span<'a> = LocatedSpan<&'a str>;
+
+/// Rows of cells, each tagged with its `(column, line)` position
+pub type Grid<T> = Vec<Vec<(IVec2, T)>>;
+
+/// 0-indexed `(column, line)` of the start of `span`, counting columns in chars
+pub fn position(span: &Span) -> IVec2 {
+    IVec2::new(
+        span.get_utf8_column() as i32 - 1,
+        span.location_line() as i32 - 1,
+    )
+}
+
+/// Space separated unsigned integers on a single line, e.g. `7 6 4 2 1`
+pub fn number_list<'a, E>(s: &'a str) -> IResult<&'a str, Vec<u64>, E>
+where
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    context(
+        "number list",
+        separated_list1(character::space1, character::u64),
+    )(s)
+}
+
+/// Space separated signed integers on a single line, e.g. `-3 10 +4`
+pub fn signed_list<'a, E>(s: &'a str) -> IResult<&'a str, Vec<i64>, E>
+where
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    context(
+        "signed list",
+        separated_list1(character::space1, character::i64),
+    )(s)
+}
+
+/// One `item` per line, up to the end of input or a blank line.
+///
+/// Unlike a plain `separated_list1`, a line that fails to parse is an error rather than the end
+/// of the list, so the error points at the broken line instead of whatever follows it.
+pub fn lines_of<'a, O, E, F>(mut item: F) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>, E>
+where
+    F: Parser<&'a str, O, E>,
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    move |s: &'a str| {
+        let (mut rest, first) = context("line", |s| item.parse(s))(s)?;
+        let mut items = vec![first];
+        loop {
+            let Ok((next, _)) = character::line_ending::<_, E>(rest) else {
+                break;
+            };
+            if next.is_empty() || character::line_ending::<_, E>(next).is_ok() {
+                break;
+            }
+            let (next, o) = context("line", |s| item.parse(s))(next)?;
+            items.push(o);
+            rest = next;
+        }
+        Ok((rest, items))
+    }
+}
+
+/// `key: value`, where the value may start on the same line or the next one.
+///
+/// Covers `190: 10 19` as well as section headers like `seed-to-soil map:\n50 98 2`.
+pub fn key_value<'a, K, V, E, FK, FV>(
+    key: FK,
+    value: FV,
+) -> impl FnMut(&'a str) -> IResult<&'a str, (K, V), E>
+where
+    FK: Parser<&'a str, K, E>,
+    FV: Parser<&'a str, V, E>,
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    context(
+        "key: value",
+        separated_pair(
+            context("key", key),
+            pair(bytes::tag(":"), character::multispace0),
+            context("value", value),
+        ),
+    )
+}
+
+/// Blocks of input separated by a blank line, e.g. a list of elves or bingo boards
+pub fn sections<'a, O, E, F>(section: F) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>, E>
+where
+    F: Parser<&'a str, O, E>,
+    E: NomParseError<&'a str> + ContextError<&'a str>,
+{
+    context(
+        "sections",
+        separated_list1(
+            pair(character::line_ending, character::line_ending),
+            context("section", section),
+        ),
+    )
+}
+
+/// Runs `parser` and pairs its output with the position it started at
+pub fn located<'a, O, E, F>(
+    mut parser: F,
+) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, (IVec2, O), E>
+where
+    F: Parser<Span<'a>, O, E>,
+{
+    move |s: Span<'a>| {
+        let p = position(&s);
+        let (rest, o) = parser.parse(s)?;
+        Ok((rest, (p, o)))
+    }
+}
+
+/// A rectangular grid where every `cell` is tagged with its `(column, line)` position.
+///
+/// Returned row by row, so `grid[y][x].0 == IVec2::new(x, y)`.
+pub fn grid<'a, O, E, F>(cell: F) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, Grid<O>, E>
+where
+    F: Parser<Span<'a>, O, E>,
+    E: NomParseError<Span<'a>> + ContextError<Span<'a>>,
+{
+    context(
+        "grid",
+        separated_list1(
+            character::line_ending,
+            many1(context("cell", located(cell))),
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nom::{combinator::value, error::VerboseError};
+
+    type Res<'a, O> = IResult<&'a str, O, VerboseError<&'a str>>;
+
+    #[test]
+    fn number_list_works() {
+        let r: Res<_> = number_list("7 6 4 2 1\n8 6");
+        assert_eq!(r.unwrap(), ("\n8 6", vec![7, 6, 4, 2, 1]));
+    }
+
+    #[test]
+    fn signed_list_works() {
+        let r: Res<_> = signed_list("-3 10 +4");
+        assert_eq!(r.unwrap(), ("", vec![-3, 10, 4]));
+    }
+
+    #[test]
+    fn lines_of_works() {
+        let input = "3   4\n4   3\r\n2   5";
+        let r: Res<_> = lines_of(separated_pair(
+            character::u64,
+            character::space1,
+            character::u64,
+        ))(input);
+        assert_eq!(r.unwrap(), ("", vec![(3, 4), (4, 3), (2, 5)]));
+    }
+
+    #[test]
+    fn key_value_same_line() {
+        let r: Res<_> = key_value(character::i64, number_list)("3267: 81 40 27");
+        assert_eq!(r.unwrap(), ("", (3267, vec![81, 40, 27])));
+    }
+
+    #[test]
+    fn key_value_next_line() {
+        let input = "seed-to-soil map:\n50 98 2";
+        let r: Res<_> = key_value(bytes::is_not(":"), number_list)(input);
+        assert_eq!(r.unwrap(), ("", ("seed-to-soil map", vec![50, 98, 2])));
+    }
+
+    #[test]
+    fn sections_works() {
+        let input = "1000\n2000\n\n4000\n\n5000\n6000";
+        let r: Res<_> = sections(lines_of(character::u64))(input);
+        assert_eq!(
+            r.unwrap(),
+            ("", vec![vec![1000, 2000], vec![4000], vec![5000, 6000]])
+        );
+    }
+
+    #[test]
+    fn grid_positions() {
+        let input = Span::new(".#\n#.");
+        let r: IResult<_, _, VerboseError<Span>> = grid(nom::branch::alt((
+            value(false, bytes::tag(".")),
+            value(true, bytes::tag("#")),
+        )))(input);
+        let (rest, g) = r.unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(
+            g,
+            vec![
+                vec![(IVec2::new(0, 0), false), (IVec2::new(1, 0), true)],
+                vec![(IVec2::new(0, 1), true), (IVec2::new(1, 1), false)],
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_carry_context() {
+        let r: Res<_> = key_value(character::i64, number_list)("190 10 19");
+        let nom::Err::Error(e) = r.unwrap_err() else {
+            panic!("expected a recoverable error")
+        };
+        let contexts = e
+            .errors
+            .iter()
+            .filter_map(|(_, kind)| match kind {
+                nom::error::VerboseErrorKind::Context(c) => Some(*c),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(contexts, vec!["key: value"]);
+    }
+}
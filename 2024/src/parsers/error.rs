@@ -0,0 +1,148 @@
+use super::Span;
+use nom::{
+    character::complete as character,
+    combinator::all_consuming,
+    error::{VerboseError, VerboseErrorKind},
+    sequence::terminated,
+    Finish, Parser,
+};
+use std::fmt;
+
+/// A parse failure pinned to the line and column of the offending input.
+///
+/// `Display` renders the line with a caret under the column, e.g.
+/// ```text
+/// expected key: value at line 2, column 4
+///   |
+/// 2 | 190 10 19
+///   |    ^
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-indexed line of the offending input
+    pub line: usize,
+    /// 1-indexed column (in chars) of the offending input
+    pub column: usize,
+    /// The full text of the offending line
+    pub snippet: String,
+    /// Innermost `context` label, or the nom error kind if there was none
+    pub expected: String,
+}
+
+impl ParseError {
+    /// Locate the byte `offset` inside `input`
+    pub fn at(input: &str, offset: usize, expected: impl Into<String>) -> Self {
+        let offset = offset.min(input.len());
+        let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[offset..]
+            .find('\n')
+            .map_or(input.len(), |i| offset + i);
+
+        Self {
+            line: input[..offset].matches('\n').count() + 1,
+            column: input[line_start..offset].chars().count() + 1,
+            snippet: input[line_start..line_end]
+                .trim_end_matches('\r')
+                .to_string(),
+            expected: expected.into(),
+        }
+    }
+
+    fn from_verbose<I>(input: &str, e: VerboseError<I>, offset_of: impl Fn(&I) -> usize) -> Self {
+        let offset = e.errors.first().map_or(input.len(), |(i, _)| offset_of(i));
+        let expected = e
+            .errors
+            .iter()
+            .find_map(|(_, kind)| match kind {
+                VerboseErrorKind::Context(c) => Some(c.to_string()),
+                _ => None,
+            })
+            .or_else(|| {
+                e.errors.first().map(|(_, kind)| match kind {
+                    VerboseErrorKind::Char(c) => format!("'{c}'"),
+                    VerboseErrorKind::Nom(kind) => kind.description().to_lowercase(),
+                    VerboseErrorKind::Context(c) => c.to_string(),
+                })
+            })
+            .unwrap_or_else(|| "valid input".to_string());
+        Self::at(input, offset, expected)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let gutter = " ".repeat(self.line.to_string().len());
+        writeln!(
+            f,
+            "expected {} at line {}, column {}",
+            self.expected, self.line, self.column
+        )?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{} | {}", self.line, self.snippet)?;
+        write!(f, "{gutter} | {}^", " ".repeat(self.column - 1))
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Run `parser` over the whole of `input`, allowing only trailing whitespace to be left over
+pub fn parse_all<'a, O, F>(input: &'a str, parser: F) -> Result<O, ParseError>
+where
+    F: Parser<&'a str, O, VerboseError<&'a str>>,
+{
+    all_consuming(terminated(parser, character::multispace0))(input)
+        .finish()
+        .map(|(_, o)| o)
+        .map_err(|e| ParseError::from_verbose(input, e, |rest| input.len() - rest.len()))
+}
+
+/// [`parse_all`] for parsers over a [`Span`]
+pub fn parse_all_span<'a, O, F>(input: &'a str, parser: F) -> Result<O, ParseError>
+where
+    F: Parser<Span<'a>, O, VerboseError<Span<'a>>>,
+{
+    all_consuming(terminated(parser, character::multispace0))(Span::new(input))
+        .finish()
+        .map(|(_, o)| o)
+        .map_err(|e| ParseError::from_verbose(input, e, |rest| rest.location_offset()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use nom::character::complete as character;
+
+    #[test]
+    fn points_at_offending_column() {
+        let input = "190: 10 19\n3267: 81 40 27\n83 17 5\n";
+        let e = parse_all(input, lines_of(key_value(character::i64, number_list))).unwrap_err();
+        assert_eq!((e.line, e.column), (3, 3));
+        assert_eq!(e.snippet, "83 17 5");
+        assert_eq!(e.expected, "key: value");
+        assert_eq!(
+            e.to_string(),
+            "expected key: value at line 3, column 3\n  |\n3 | 83 17 5\n  |   ^"
+        );
+    }
+
+    #[test]
+    fn trailing_garbage_is_an_error() {
+        let e = parse_all("1 2 3\n4 5 x\n", lines_of(number_list)).unwrap_err();
+        assert_eq!((e.line, e.column), (2, 5));
+    }
+
+    #[test]
+    fn trailing_newline_is_fine() {
+        assert_eq!(
+            parse_all("1 2\n3 4\n", lines_of(number_list)),
+            Ok(vec![vec![1, 2], vec![3, 4]])
+        );
+    }
+
+    #[test]
+    fn span_errors_use_location() {
+        let e = parse_all_span("..\n.x", grid(nom::bytes::complete::tag("."))).unwrap_err();
+        assert_eq!((e.line, e.column), (2, 2));
+        assert_eq!(e.snippet, ".x");
+    }
+}
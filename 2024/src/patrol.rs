@@ -0,0 +1,432 @@
+//! A guard patrolling a lab, walking straight ahead and turning right at every obstacle, like
+//! day 6.
+//!
+//! For every cell and heading the [`Lab`] knows where the guard would stop in front of the next
+//! obstacle, so walks move a whole segment at a time. One extra obstacle can be dropped in for a
+//! walk without touching those tables, it only has to be checked against the segment being
+//! walked.
+use anyhow::{bail, ensure, Result};
+use glam::IVec2;
+use rayon::prelude::*;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Heading {
+    #[default]
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Heading {
+    pub const ALL: [Heading; 4] = [Heading::North, Heading::East, Heading::South, Heading::West];
+
+    pub fn turn_right(self) -> Self {
+        match self {
+            Heading::North => Heading::East,
+            Heading::East => Heading::South,
+            Heading::South => Heading::West,
+            Heading::West => Heading::North,
+        }
+    }
+
+    /// A step forwards, with `y` growing southwards
+    pub fn offset(self) -> IVec2 {
+        match self {
+            Heading::North => IVec2::new(0, -1),
+            Heading::East => IVec2::new(1, 0),
+            Heading::South => IVec2::new(0, 1),
+            Heading::West => IVec2::new(-1, 0),
+        }
+    }
+
+    pub fn is_vertical(self) -> bool {
+        matches!(self, Heading::North | Heading::South)
+    }
+
+    /// How the guard is drawn on the map
+    pub fn symbol(self) -> char {
+        match self {
+            Heading::North => '^',
+            Heading::East => '>',
+            Heading::South => 'v',
+            Heading::West => '<',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Guard {
+    pub pos: IVec2,
+    pub heading: Heading,
+}
+
+/// A straight stretch of a walk, both ends included
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment {
+    pub from: IVec2,
+    pub to: IVec2,
+    pub heading: Heading,
+}
+
+impl Segment {
+    pub fn cells(&self) -> impl Iterator<Item = IVec2> {
+        let d = self.heading.offset();
+        let len = (self.to - self.from).dot(d);
+        let from = self.from;
+        (0..=len).map(move |i| from + d * i)
+    }
+}
+
+/// The walk a guard takes, until leaving the lab or going round in a loop
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Patrol {
+    pub segments: Vec<Segment>,
+    pub loops: bool,
+}
+
+impl Patrol {
+    /// Every position and heading in order, a step at a time. Turns show up as the same
+    /// position twice.
+    pub fn steps(&self) -> impl Iterator<Item = Guard> + '_ {
+        self.segments.iter().flat_map(|s| {
+            s.cells().map(|pos| Guard {
+                pos,
+                heading: s.heading,
+            })
+        })
+    }
+
+    pub fn visited(&self) -> HashSet<IVec2> {
+        self.steps().map(|g| g.pos).collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Lab {
+    size: IVec2,
+    blocked: Vec<bool>,
+    /// Where the guard stops in front of the next obstacle from every cell and heading, `None`
+    /// if the guard walks out of the lab instead
+    jumps: Vec<[Option<IVec2>; 4]>,
+}
+
+impl Lab {
+    pub fn new(size: IVec2, obstacles: &HashSet<IVec2>) -> Self {
+        let cells = (0..size.y)
+            .flat_map(|y| (0..size.x).map(move |x| IVec2::new(x, y)))
+            .collect::<Vec<_>>();
+        let mut lab = Self {
+            size,
+            blocked: cells.iter().map(|p| obstacles.contains(p)).collect(),
+            jumps: vec![[None; 4]; cells.len()],
+        };
+        for (h, heading) in Heading::ALL.into_iter().enumerate() {
+            let d = heading.offset();
+            // the cell ahead is always done first
+            let mut order = cells.clone();
+            order.sort_by_key(|p| -p.dot(d));
+            for p in order {
+                let ahead = p + d;
+                let jump = if !lab.contains(ahead) {
+                    None
+                } else if lab.is_blocked(ahead) {
+                    Some(p)
+                } else {
+                    lab.jumps[lab.index(ahead)][h]
+                };
+                let i = lab.index(p);
+                lab.jumps[i][h] = jump;
+            }
+        }
+        lab
+    }
+
+    /// The map with `#` for obstacles and the guard as one of `^>v<`
+    pub fn parse(input: &str) -> Result<(Self, Guard)> {
+        let mut obstacles = HashSet::new();
+        let mut guard = None;
+        let mut size = IVec2::ZERO;
+        for (y, line) in input.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                let pos = IVec2::new(x as i32, y as i32);
+                let heading = match c {
+                    '.' => continue,
+                    '#' => {
+                        obstacles.insert(pos);
+                        continue;
+                    }
+                    _ => match Heading::ALL.into_iter().find(|h| h.symbol() == c) {
+                        Some(heading) => heading,
+                        None => bail!("Unexpected '{}' at {}", c, pos),
+                    },
+                };
+                ensure!(guard.is_none(), "There's more than one guard");
+                guard = Some(Guard { pos, heading });
+            }
+            size = size.max(IVec2::new(line.chars().count() as i32, y as i32 + 1));
+        }
+        let Some(guard) = guard else {
+            bail!("There's no guard on the map");
+        };
+        Ok((Self::new(size, &obstacles), guard))
+    }
+
+    pub fn size(&self) -> IVec2 {
+        self.size
+    }
+
+    pub fn contains(&self, p: IVec2) -> bool {
+        p.cmpge(IVec2::ZERO).all() && p.cmplt(self.size).all()
+    }
+
+    fn index(&self, p: IVec2) -> usize {
+        (p.y * self.size.x + p.x) as usize
+    }
+
+    pub fn is_blocked(&self, p: IVec2) -> bool {
+        self.contains(p) && self.blocked[self.index(p)]
+    }
+
+    /// Where the guard stops walking straight ahead, `None` if the guard leaves the lab.
+    /// `extra` is an obstacle that isn't in the tables.
+    pub fn next_stop(&self, guard: Guard, extra: Option<IVec2>) -> Option<IVec2> {
+        let d = guard.heading.offset();
+        let stop = self.jumps[self.index(guard.pos)][guard.heading as usize];
+        // an obstacle outside the lab can't stop anyone
+        if let Some(o) = extra.filter(|&o| self.contains(o)) {
+            let ahead = o - guard.pos;
+            // on the line in front, and no further than the obstacle or edge that would stop the
+            // guard anyway
+            let reach = stop.map_or(i32::MAX, |s| (s - guard.pos).dot(d) + 1);
+            if ahead.perp_dot(d) == 0 && ahead.dot(d) > 0 && ahead.dot(d) <= reach {
+                return Some(o - d);
+            }
+        }
+        stop
+    }
+
+    /// The last cell before walking out of the lab
+    fn edge(&self, guard: Guard) -> IVec2 {
+        let mut pos = guard.pos;
+        while self.contains(pos + guard.heading.offset()) {
+            pos += guard.heading.offset();
+        }
+        pos
+    }
+
+    /// Walk from `guard` with an optional extra obstacle
+    pub fn patrol(&self, mut guard: Guard, extra: Option<IVec2>) -> Patrol {
+        let mut segments = vec![];
+        let mut turns = HashSet::new();
+        loop {
+            let Some(to) = self.next_stop(guard, extra) else {
+                segments.push(Segment {
+                    from: guard.pos,
+                    to: self.edge(guard),
+                    heading: guard.heading,
+                });
+                return Patrol {
+                    segments,
+                    loops: false,
+                };
+            };
+            segments.push(Segment {
+                from: guard.pos,
+                to,
+                heading: guard.heading,
+            });
+            guard = Guard {
+                pos: to,
+                heading: guard.heading.turn_right(),
+            };
+            if !turns.insert(guard) {
+                return Patrol {
+                    segments,
+                    loops: true,
+                };
+            }
+        }
+    }
+
+    /// Whether the guard ends up going round in a loop, [`Lab::patrol`] without keeping the
+    /// segments
+    pub fn loops(&self, mut guard: Guard, extra: Option<IVec2>) -> bool {
+        let mut turns = HashSet::new();
+        while let Some(to) = self.next_stop(guard, extra) {
+            guard = Guard {
+                pos: to,
+                heading: guard.heading.turn_right(),
+            };
+            if !turns.insert(guard) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Every place a single extra obstacle would trap the guard from `start` in a loop.
+    ///
+    /// Only cells on the guard's route can change anything. Each one is tried the first time the
+    /// guard is about to step into it, and the walk picks up from there since everything before
+    /// is unchanged.
+    pub fn trapping_obstacles(&self, start: Guard) -> Vec<IVec2> {
+        let mut seen = HashSet::from([start.pos]);
+        let mut candidates = vec![];
+        for guard in self.patrol(start, None).steps() {
+            seen.insert(guard.pos);
+            let ahead = guard.pos + guard.heading.offset();
+            if self.contains(ahead) && !self.is_blocked(ahead) && !seen.contains(&ahead) {
+                candidates.push((ahead, guard));
+            }
+        }
+        candidates
+            .into_par_iter()
+            .filter(|&(obstacle, guard)| self.loops(guard, Some(obstacle)))
+            .map(|(obstacle, _)| obstacle)
+            .collect()
+    }
+
+    /// A picture of the patrol with `|` and `-` for the way the guard went, `+` where the route
+    /// crosses itself, `^` at the start and `O` for the extra obstacle
+    pub fn render(&self, start: Guard, patrol: &Patrol, extra: Option<IVec2>) -> String {
+        let mut vertical = HashSet::new();
+        let mut horizontal = HashSet::new();
+        for guard in patrol.steps() {
+            if guard.heading.is_vertical() {
+                vertical.insert(guard.pos);
+            } else {
+                horizontal.insert(guard.pos);
+            }
+        }
+        let mut s = String::new();
+        for y in 0..self.size.y {
+            for x in 0..self.size.x {
+                let p = IVec2::new(x, y);
+                s.push(match (vertical.contains(&p), horizontal.contains(&p)) {
+                    _ if p == start.pos => start.heading.symbol(),
+                    _ if Some(p) == extra => 'O',
+                    _ if self.is_blocked(p) => '#',
+                    (true, true) => '+',
+                    (true, false) => '|',
+                    (false, true) => '-',
+                    (false, false) => '.',
+                });
+            }
+            s.push('\n');
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAB: &str = "....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...
+";
+
+    #[test]
+    fn jumps() {
+        let (lab, guard) = Lab::parse(LAB).unwrap();
+        assert_eq!(guard.pos, IVec2::new(4, 6));
+        assert_eq!(lab.next_stop(guard, None), Some(IVec2::new(4, 1)));
+        let west = Guard {
+            pos: IVec2::new(4, 6),
+            heading: Heading::West,
+        };
+        assert_eq!(lab.next_stop(west, None), Some(IVec2::new(2, 6)));
+        // the extra obstacle only matters if it comes first
+        assert_eq!(
+            lab.next_stop(guard, Some(IVec2::new(4, 3))),
+            Some(IVec2::new(4, 4))
+        );
+        assert_eq!(
+            lab.next_stop(guard, Some(IVec2::new(4, 0))),
+            Some(IVec2::new(4, 1))
+        );
+        assert_eq!(
+            lab.next_stop(guard, Some(IVec2::new(5, 3))),
+            Some(IVec2::new(4, 1))
+        );
+        let east = Guard {
+            pos: IVec2::new(9, 0),
+            heading: Heading::East,
+        };
+        assert_eq!(lab.next_stop(east, None), None);
+        // obstacles outside the lab are ignored
+        assert_eq!(lab.next_stop(east, Some(IVec2::new(11, 0))), None);
+        let out = lab.patrol(east, Some(IVec2::new(11, 0)));
+        assert_eq!(out.visited().len(), 1);
+    }
+
+    #[test]
+    fn patrols() {
+        let (lab, guard) = Lab::parse(LAB).unwrap();
+        let patrol = lab.patrol(guard, None);
+        assert!(!patrol.loops);
+        assert_eq!(patrol.visited().len(), 41);
+        assert_eq!(patrol.segments.last().unwrap().to, IVec2::new(7, 9));
+
+        let obstacle = IVec2::new(3, 6);
+        let trapped = lab.patrol(guard, Some(obstacle));
+        assert!(trapped.loops);
+        assert_eq!(
+            lab.render(guard, &trapped, Some(obstacle)),
+            "....#.....
+....+---+#
+....|...|.
+..#.|...|.
+....|..#|.
+....|...|.
+.#.O^---+.
+........#.
+#.........
+......#...
+"
+        );
+    }
+
+    #[test]
+    fn trapping() {
+        let (lab, guard) = Lab::parse(LAB).unwrap();
+        let mut obstacles = lab.trapping_obstacles(guard);
+        obstacles.sort_by_key(|p| (p.y, p.x));
+        assert_eq!(
+            obstacles,
+            [
+                IVec2::new(3, 6),
+                IVec2::new(6, 7),
+                IVec2::new(7, 7),
+                IVec2::new(1, 8),
+                IVec2::new(3, 8),
+                IVec2::new(7, 9),
+            ]
+        );
+    }
+
+    #[test]
+    fn bad_maps() {
+        assert!(Lab::parse("..\n.#").is_err());
+        assert!(Lab::parse("^^").is_err());
+        assert!(Lab::parse("^x").is_err());
+    }
+
+    #[test]
+    fn render_keeps_the_heading() {
+        let (lab, guard) = Lab::parse("...\n.<.\n...").unwrap();
+        assert_eq!(guard.heading, Heading::West);
+        let patrol = lab.patrol(guard, None);
+        assert_eq!(lab.render(guard, &patrol, None), "...\n-<.\n...\n");
+    }
+}
@@ -0,0 +1,261 @@
+//! Picking function calls like `mul(2,4)` out of corrupted memory, as in day 3.
+//!
+//! A [`Scanner`] is given the [`Pattern`]s of the calls it's looking for and walks the input a
+//! character at a time, yielding every well formed call along with where it was found. Anything
+//! else is junk and skipped. Two of the patterns can be set up as switches that turn the calls
+//! after them off and back on.
+use nom::{
+    bytes::complete::{tag, take_while_m_n},
+    character::complete::char,
+    combinator::map_res,
+    multi::separated_list0,
+    sequence::{delimited, preceded},
+    IResult,
+};
+use std::ops::RangeInclusive;
+
+/// The shape of a call, its name followed by unsigned numbers in brackets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    pub name: String,
+    /// How many arguments it takes
+    pub arity: RangeInclusive<usize>,
+    /// The most digits an argument can have
+    pub max_digits: usize,
+}
+
+impl Pattern {
+    /// A call taking exactly `arity` arguments, each as wide as fits in a `u64`
+    pub fn new(name: &str, arity: usize) -> Self {
+        Self {
+            name: name.to_string(),
+            arity: arity..=arity,
+            max_digits: 18,
+        }
+    }
+
+    pub fn with_arity(mut self, arity: RangeInclusive<usize>) -> Self {
+        self.arity = arity;
+        self
+    }
+
+    pub fn with_max_digits(mut self, max_digits: usize) -> Self {
+        self.max_digits = max_digits;
+        self
+    }
+
+    /// The arguments of a call at the start of `s`, an argument too big for a `u64` doesn't match
+    fn call<'a>(&self, s: &'a str) -> IResult<&'a str, Vec<u64>> {
+        let argument = map_res(
+            take_while_m_n(1, self.max_digits, |c: char| c.is_ascii_digit()),
+            str::parse,
+        );
+        preceded(
+            tag(self.name.as_str()),
+            delimited(char('('), separated_list0(char(','), argument), char(')')),
+        )(s)
+    }
+}
+
+/// A call found in the input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Call<'a> {
+    pub name: &'a str,
+    pub args: Vec<u64>,
+    /// Byte offset of the start of the call
+    pub offset: usize,
+    /// The call as written, so `offset + text.len()` is just past its end
+    pub text: &'a str,
+    /// Whether the calls were switched on when this one was found. Switches are always
+    /// enabled themselves.
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scanner {
+    patterns: Vec<Pattern>,
+    /// Names of the calls that turn the others (off, on)
+    switches: Option<(String, String)>,
+}
+
+impl Scanner {
+    pub fn new(patterns: Vec<Pattern>) -> Self {
+        Self {
+            patterns,
+            switches: None,
+        }
+    }
+
+    /// Calls to `off` disable the calls after them until a call to `on`. Neither needs to be
+    /// one of the patterns, they're added as calls without arguments if they aren't.
+    pub fn with_switches(mut self, off: &str, on: &str) -> Self {
+        for name in [off, on] {
+            if !self.patterns.iter().any(|p| p.name == name) {
+                self.patterns.push(Pattern::new(name, 0));
+            }
+        }
+        self.switches = Some((off.to_string(), on.to_string()));
+        self
+    }
+
+    /// Every call in `input` in order, starting enabled
+    pub fn scan<'s, 'a>(&'s self, input: &'a str) -> Calls<'s, 'a> {
+        Calls {
+            scanner: self,
+            input,
+            offset: 0,
+            enabled: true,
+        }
+    }
+
+    /// The calls that are enabled, without the switches
+    pub fn enabled<'s, 'a>(&'s self, input: &'a str) -> impl Iterator<Item = Call<'a>> + 's
+    where
+        'a: 's,
+    {
+        let switches = self.switches.clone();
+        self.scan(input).filter(move |call| {
+            call.enabled
+                && !switches
+                    .as_ref()
+                    .is_some_and(|(off, on)| call.name == off || call.name == on)
+        })
+    }
+}
+
+/// The calls being found, see [`Scanner::scan`]
+pub struct Calls<'s, 'a> {
+    scanner: &'s Scanner,
+    input: &'a str,
+    offset: usize,
+    enabled: bool,
+}
+
+impl<'a> Calls<'_, 'a> {
+    /// Whether the calls are currently switched on
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// The first pattern that matches at the current offset
+    fn call_here(&self) -> Option<Call<'a>> {
+        let rest = &self.input[self.offset..];
+        self.scanner.patterns.iter().find_map(|pattern| {
+            let (after, args) = pattern.call(rest).ok()?;
+            if !pattern.arity.contains(&args.len()) {
+                return None;
+            }
+            Some(Call {
+                name: &rest[..pattern.name.len()],
+                args,
+                offset: self.offset,
+                text: &rest[..rest.len() - after.len()],
+                enabled: self.enabled,
+            })
+        })
+    }
+}
+
+impl<'a> Iterator for Calls<'_, 'a> {
+    type Item = Call<'a>;
+
+    fn next(&mut self) -> Option<Call<'a>> {
+        while self.offset < self.input.len() {
+            if let Some(mut call) = self.call_here() {
+                self.offset += call.text.len();
+                if let Some((off, on)) = &self.scanner.switches {
+                    if call.name == off {
+                        self.enabled = false;
+                        call.enabled = true;
+                    } else if call.name == on {
+                        self.enabled = true;
+                        call.enabled = true;
+                    }
+                }
+                return Some(call);
+            }
+            self.offset += self.input[self.offset..]
+                .chars()
+                .next()
+                .map_or(1, char::len_utf8);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEMORY: &str =
+        "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+    fn mul() -> Pattern {
+        Pattern::new("mul", 2).with_max_digits(3)
+    }
+
+    #[test]
+    fn offsets() {
+        let scanner = Scanner::new(vec![mul()]);
+        let calls = scanner
+            .scan(MEMORY)
+            .map(|c| (c.offset, c.text, c.args))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            calls,
+            [
+                (1, "mul(2,4)", vec![2, 4]),
+                (28, "mul(5,5)", vec![5, 5]),
+                (48, "mul(11,8)", vec![11, 8]),
+                (64, "mul(8,5)", vec![8, 5]),
+            ]
+        );
+        assert_eq!(&MEMORY[48..57], "mul(11,8)");
+    }
+
+    #[test]
+    fn switching() {
+        let scanner = Scanner::new(vec![mul()]).with_switches("don't", "do");
+        let names = scanner
+            .scan(MEMORY)
+            .map(|c| (c.name, c.enabled))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                ("mul", true),
+                ("don't", true),
+                ("mul", false),
+                ("mul", false),
+                ("do", true),
+                ("mul", true),
+            ]
+        );
+        let products = scanner
+            .enabled(MEMORY)
+            .map(|c| c.args[0] * c.args[1])
+            .sum::<u64>();
+        assert_eq!(products, 48);
+    }
+
+    #[test]
+    fn limits() {
+        let scanner = Scanner::new(vec![mul(), Pattern::new("max", 1).with_arity(1..=3)]);
+        let found = |s| scanner.scan(s).map(|c| c.text).collect::<Vec<_>>();
+        assert_eq!(found("mul(1234,5)mul(123,4)"), ["mul(123,4)"]);
+        assert_eq!(
+            found("mul(1)mul(1,2,3)mul(,2)mul(-1,2)"),
+            Vec::<&str>::new()
+        );
+        assert_eq!(
+            found("max()max(1)max(1,2,3,4)max(1,2,3)"),
+            ["max(1)", "max(1,2,3)"]
+        );
+        // calls can start inside junk that looked like the start of another call
+        assert_eq!(found("mul(mul(1,2)é"), ["mul(1,2)"]);
+        // arguments too big for a u64 don't match, however many digits are allowed
+        let wide = Scanner::new(vec![Pattern::new("mul", 1).with_max_digits(25)]);
+        let calls = wide.scan("mul(99999999999999999999)mul(18446744073709551615)");
+        assert_eq!(calls.map(|c| c.args).collect::<Vec<_>>(), [vec![u64::MAX]]);
+    }
+}
@@ -0,0 +1,245 @@
+//! Positional tokenizer for puzzles where the input is a picture of numbers, words and symbols
+//! scattered over a grid of blanks, like an engine schematic.
+use crate::parsers::{parse_all_span, position, ParseError, Span};
+use glam::IVec2;
+use nom::{
+    branch::alt,
+    character::complete as character,
+    combinator::{recognize, value},
+    error::VerboseError,
+    multi::many0,
+    IResult, Parser,
+};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    Number,
+    Word,
+    Symbol,
+}
+
+/// A run of characters on a single line, e.g. `467`, `abc` or `*`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    /// `(column, line)` of the first character
+    pub pos: IVec2,
+}
+
+impl<'a> Token<'a> {
+    fn from_span(kind: TokenKind, span: Span<'a>) -> Self {
+        Self {
+            kind,
+            text: span.fragment(),
+            pos: position(&span),
+        }
+    }
+
+    /// Width of the token in cells
+    pub fn len(&self) -> i32 {
+        self.text.chars().count() as i32
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// The first character of the token, handy for single cell tokens
+    pub fn char(&self) -> char {
+        self.text.chars().next().expect("tokens are never empty")
+    }
+
+    /// Parse the text of the token, e.g. `token.value::<u32>()` for a number
+    pub fn value<T: FromStr>(&self) -> Option<T> {
+        self.text.parse().ok()
+    }
+
+    /// Every cell the token covers
+    pub fn cells(&self) -> impl Iterator<Item = IVec2> + '_ {
+        (0..self.len()).map(|dx| self.pos + IVec2::new(dx, 0))
+    }
+
+    /// The ring of cells around the token, including diagonals
+    pub fn border(&self) -> impl Iterator<Item = IVec2> + '_ {
+        let (left, right) = (self.pos.x - 1, self.pos.x + self.len());
+        (left..=right)
+            .flat_map(move |x| [IVec2::new(x, self.pos.y - 1), IVec2::new(x, self.pos.y + 1)])
+            .chain([IVec2::new(left, self.pos.y), IVec2::new(right, self.pos.y)])
+    }
+}
+
+/// All tokens of a grid, indexed by the cells they cover
+#[derive(Debug, Clone, Default)]
+pub struct TokenGrid<'a> {
+    tokens: Vec<Token<'a>>,
+    cells: HashMap<IVec2, usize>,
+}
+
+impl<'a> TokenGrid<'a> {
+    /// Split `input` into numbers (runs of digits), words (runs of letters) and single character
+    /// symbols, skipping `blank` and whitespace.
+    pub fn parse(input: &'a str, blank: char) -> Result<Self, ParseError> {
+        let token = alt((
+            recognize(character::digit1).map(|s| Some(Token::from_span(TokenKind::Number, s))),
+            recognize(character::alpha1).map(|s| Some(Token::from_span(TokenKind::Word, s))),
+            value(
+                None,
+                character::satisfy(move |c| c == blank || c.is_whitespace()),
+            ),
+            recognize(character::anychar).map(|s| Some(Token::from_span(TokenKind::Symbol, s))),
+        ));
+        let tokens = parse_all_span(input, many0(token))?;
+        Ok(tokens.into_iter().flatten().collect())
+    }
+
+    /// Like [`TokenGrid::parse`], but every non blank character is its own token, so `aA0` is a
+    /// word, another word and a number rather than one word.
+    pub fn parse_cells(input: &'a str, blank: char) -> Result<Self, ParseError> {
+        fn cell(
+            blank: char,
+        ) -> impl FnMut(Span) -> IResult<Span, Option<Token>, VerboseError<Span>> {
+            move |s: Span| {
+                let (rest, c) = character::anychar(s)?;
+                let kind = match c {
+                    c if c == blank || c.is_whitespace() => return Ok((rest, None)),
+                    c if c.is_ascii_digit() => TokenKind::Number,
+                    c if c.is_alphabetic() => TokenKind::Word,
+                    _ => TokenKind::Symbol,
+                };
+                let (_, text) = recognize(character::anychar)(s)?;
+                Ok((rest, Some(Token::from_span(kind, text))))
+            }
+        }
+        let tokens = parse_all_span(input, many0(cell(blank)))?;
+        Ok(tokens.into_iter().flatten().collect())
+    }
+
+    pub fn tokens(&self) -> &[Token<'a>] {
+        &self.tokens
+    }
+
+    pub fn of_kind(&self, kind: TokenKind) -> impl Iterator<Item = &Token<'a>> {
+        self.tokens.iter().filter(move |t| t.kind == kind)
+    }
+
+    /// The token covering `pos`, if any
+    pub fn at(&self, pos: IVec2) -> Option<&Token<'a>> {
+        self.cells.get(&pos).map(|&i| &self.tokens[i])
+    }
+
+    /// Every distinct token touching `token`, including diagonally, in input order
+    pub fn adjacent(&self, token: &Token<'a>) -> Vec<&Token<'a>> {
+        let mut indices = token
+            .border()
+            .filter_map(|pos| self.cells.get(&pos).copied())
+            .collect::<Vec<_>>();
+        indices.sort_unstable();
+        indices.dedup();
+        indices.into_iter().map(|i| &self.tokens[i]).collect()
+    }
+}
+
+impl<'a> FromIterator<Token<'a>> for TokenGrid<'a> {
+    fn from_iter<T: IntoIterator<Item = Token<'a>>>(iter: T) -> Self {
+        let tokens = iter.into_iter().collect::<Vec<_>>();
+        let cells = tokens
+            .iter()
+            .enumerate()
+            .flat_map(|(i, t)| t.cells().map(move |pos| (pos, i)))
+            .collect();
+        Self { tokens, cells }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMATIC: &str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..
+";
+
+    #[test]
+    fn tokens_have_positions_and_lengths() {
+        let grid = TokenGrid::parse("..12.ab\n*..3", '.').unwrap();
+        let tokens = grid
+            .tokens()
+            .iter()
+            .map(|t| (t.kind, t.text, t.pos, t.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            vec![
+                (TokenKind::Number, "12", IVec2::new(2, 0), 2),
+                (TokenKind::Word, "ab", IVec2::new(5, 0), 2),
+                (TokenKind::Symbol, "*", IVec2::new(0, 1), 1),
+                (TokenKind::Number, "3", IVec2::new(3, 1), 1),
+            ]
+        );
+        assert_eq!(grid.at(IVec2::new(3, 0)).unwrap().text, "12");
+        assert_eq!(grid.at(IVec2::new(4, 0)), None);
+    }
+
+    #[test]
+    fn columns_count_chars() {
+        let grid = TokenGrid::parse("é·12.ab", '.').unwrap();
+        let tokens = grid
+            .tokens()
+            .iter()
+            .map(|t| (t.text, t.pos.x, t.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            vec![("é", 0, 1), ("·", 1, 1), ("12", 2, 2), ("ab", 5, 2)]
+        );
+        assert_eq!(grid.at(IVec2::new(3, 0)).unwrap().text, "12");
+    }
+
+    #[test]
+    fn parse_cells_splits_runs() {
+        let grid = TokenGrid::parse_cells("aA0.\n..12", '.').unwrap();
+        let texts = grid.tokens().iter().map(|t| t.text).collect::<Vec<_>>();
+        assert_eq!(texts, vec!["a", "A", "0", "1", "2"]);
+        assert_eq!(grid.tokens()[4].pos, IVec2::new(3, 1));
+    }
+
+    #[test]
+    fn part_numbers() {
+        let grid = TokenGrid::parse(SCHEMATIC, '.').unwrap();
+        let sum: u32 = grid
+            .of_kind(TokenKind::Number)
+            .filter(|n| grid.adjacent(n).iter().any(|t| t.kind == TokenKind::Symbol))
+            .filter_map(|n| n.value::<u32>())
+            .sum();
+        assert_eq!(sum, 4361);
+    }
+
+    #[test]
+    fn gear_ratios() {
+        let grid = TokenGrid::parse(SCHEMATIC, '.').unwrap();
+        let sum: u32 = grid
+            .of_kind(TokenKind::Symbol)
+            .filter(|s| s.text == "*")
+            .map(|s| grid.adjacent(s))
+            .filter(|numbers| numbers.len() == 2)
+            .map(|numbers| {
+                numbers
+                    .iter()
+                    .filter_map(|n| n.value::<u32>())
+                    .product::<u32>()
+            })
+            .sum();
+        assert_eq!(sum, 467835);
+    }
+}
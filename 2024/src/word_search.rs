@@ -0,0 +1,292 @@
+//! Word search over a grid of letters, like the XMAS puzzle in day 4.
+//!
+//! [`WordSearch::find`] looks for a word along straight lines in any set of [`Direction`]s.
+//! Shapes that aren't a straight line, like two crossed words, are written as a [`Stencil`]
+//! which can be rotated and reflected before matching.
+use glam::IVec2;
+use std::str::FromStr;
+
+/// The eight ways a word can run, with `y` growing downwards
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::Up,
+        Direction::UpRight,
+        Direction::Right,
+        Direction::DownRight,
+        Direction::Down,
+        Direction::DownLeft,
+        Direction::Left,
+        Direction::UpLeft,
+    ];
+    pub const STRAIGHT: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+    pub const DIAGONAL: [Direction; 4] = [
+        Direction::UpRight,
+        Direction::DownRight,
+        Direction::DownLeft,
+        Direction::UpLeft,
+    ];
+
+    /// The step from one letter to the next
+    pub fn offset(self) -> IVec2 {
+        match self {
+            Direction::Up => IVec2::new(0, -1),
+            Direction::UpRight => IVec2::new(1, -1),
+            Direction::Right => IVec2::new(1, 0),
+            Direction::DownRight => IVec2::new(1, 1),
+            Direction::Down => IVec2::new(0, 1),
+            Direction::DownLeft => IVec2::new(-1, 1),
+            Direction::Left => IVec2::new(-1, 0),
+            Direction::UpLeft => IVec2::new(-1, -1),
+        }
+    }
+}
+
+/// A word found in the grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Match {
+    /// `(column, line)` of the first letter
+    pub start: IVec2,
+    pub direction: Direction,
+}
+
+impl Match {
+    /// Where each letter of a `len` letter word is
+    pub fn cells(&self, len: usize) -> impl Iterator<Item = IVec2> + '_ {
+        (0..len as i32).map(|i| self.start + self.direction.offset() * i)
+    }
+}
+
+/// Letters that must be at fixed offsets from each other, any other cells don't matter
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Stencil {
+    /// Sorted, and shifted so the smallest `x` and `y` are both 0
+    cells: Vec<(IVec2, char)>,
+}
+
+impl Stencil {
+    pub fn new(cells: impl IntoIterator<Item = (IVec2, char)>) -> Self {
+        let cells = cells.into_iter().collect::<Vec<_>>();
+        let min = cells
+            .iter()
+            .map(|c| c.0)
+            .reduce(IVec2::min)
+            .unwrap_or(IVec2::ZERO);
+        let mut cells = cells
+            .into_iter()
+            .map(|(pos, c)| (pos - min, c))
+            .collect::<Vec<_>>();
+        cells.sort_by_key(|(pos, c)| (pos.y, pos.x, *c));
+        Self { cells }
+    }
+
+    pub fn cells(&self) -> &[(IVec2, char)] {
+        &self.cells
+    }
+
+    /// A quarter turn clockwise
+    pub fn rotate(&self) -> Self {
+        Self::new(
+            self.cells
+                .iter()
+                .map(|&(pos, c)| (IVec2::new(-pos.y, pos.x), c)),
+        )
+    }
+
+    /// Mirrored left to right
+    pub fn reflect(&self) -> Self {
+        Self::new(
+            self.cells
+                .iter()
+                .map(|&(pos, c)| (IVec2::new(-pos.x, pos.y), c)),
+        )
+    }
+
+    /// The four rotations of the stencil, and with `reflect` their mirror images too. Stencils
+    /// that look the same are only included once.
+    pub fn variants(&self, reflect: bool) -> Vec<Stencil> {
+        let mut variants = vec![];
+        let mut stencil = self.clone();
+        for _ in 0..4 {
+            if reflect {
+                let mirror = stencil.reflect();
+                if !variants.contains(&mirror) {
+                    variants.push(mirror);
+                }
+            }
+            let next = stencil.rotate();
+            if !variants.contains(&stencil) {
+                variants.push(stencil);
+            }
+            stencil = next;
+        }
+        variants
+    }
+}
+
+impl FromStr for Stencil {
+    type Err = std::convert::Infallible;
+    /// A picture of the letters, with `.` or a space for cells that can be anything
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::new(s.lines().enumerate().flat_map(|(y, line)| {
+            line.chars()
+                .enumerate()
+                .filter(|(_, c)| *c != '.' && *c != ' ')
+                .map(move |(x, c)| (IVec2::new(x as i32, y as i32), c))
+        })))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordSearch {
+    rows: Vec<Vec<char>>,
+}
+
+impl WordSearch {
+    pub fn new(input: &str) -> Self {
+        Self {
+            rows: input.lines().map(|l| l.chars().collect()).collect(),
+        }
+    }
+
+    pub fn get(&self, pos: IVec2) -> Option<char> {
+        if pos.x < 0 || pos.y < 0 {
+            return None;
+        }
+        self.rows.get(pos.y as usize)?.get(pos.x as usize).copied()
+    }
+
+    /// Every cell, row by row
+    pub fn positions(&self) -> impl Iterator<Item = IVec2> + '_ {
+        self.rows
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| (0..row.len()).map(move |x| IVec2::new(x as i32, y as i32)))
+    }
+
+    /// Whether `word` is spelled from `start` going in `direction`
+    pub fn spells(&self, word: &str, start: IVec2, direction: Direction) -> bool {
+        word.chars()
+            .enumerate()
+            .all(|(i, c)| self.get(start + direction.offset() * i as i32) == Some(c))
+    }
+
+    /// Every place `word` appears going in one of `directions`
+    pub fn find<'a>(
+        &'a self,
+        word: &'a str,
+        directions: &'a [Direction],
+    ) -> impl Iterator<Item = Match> + 'a {
+        self.positions().flat_map(move |start| {
+            directions
+                .iter()
+                .filter(move |&&direction| self.spells(word, start, direction))
+                .map(move |&direction| Match { start, direction })
+        })
+    }
+
+    /// Whether `stencil` fits with its top left corner at `corner`
+    pub fn fits(&self, stencil: &Stencil, corner: IVec2) -> bool {
+        stencil
+            .cells()
+            .iter()
+            .all(|&(pos, c)| self.get(corner + pos) == Some(c))
+    }
+
+    /// The top left corner of every place one of the `stencils` fits, each place counted once
+    /// even if several stencils fit there
+    pub fn find_stencils(&self, stencils: &[Stencil]) -> Vec<IVec2> {
+        self.positions()
+            .filter(|&corner| stencils.iter().any(|s| self.fits(s, corner)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRID: &str = "XMAS
+MMAS
+AXAA
+SSXS
+";
+
+    #[test]
+    fn words_in_directions() {
+        let search = WordSearch::new(GRID);
+        let all = search.find("XMAS", &Direction::ALL).collect::<Vec<_>>();
+        assert_eq!(
+            all,
+            [
+                Match {
+                    start: IVec2::new(0, 0),
+                    direction: Direction::Right
+                },
+                Match {
+                    start: IVec2::new(0, 0),
+                    direction: Direction::DownRight
+                },
+                Match {
+                    start: IVec2::new(0, 0),
+                    direction: Direction::Down
+                },
+            ]
+        );
+        assert_eq!(search.find("XMAS", &Direction::STRAIGHT).count(), 2);
+        assert_eq!(search.find("XA", &Direction::DIAGONAL).count(), 2);
+        assert_eq!(
+            all[1].cells(4).collect::<Vec<_>>(),
+            [
+                IVec2::new(0, 0),
+                IVec2::new(1, 1),
+                IVec2::new(2, 2),
+                IVec2::new(3, 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn stencil_variants() {
+        let cross = "M.S\n.A.\nM.S".parse::<Stencil>().unwrap();
+        let variants = cross.variants(true);
+        assert_eq!(variants.len(), 4);
+        assert_eq!(cross.rotate().rotate().rotate().rotate(), cross);
+        assert_eq!(cross.rotate(), "M.M\n.A.\nS.S".parse::<Stencil>().unwrap());
+
+        // an L only looks the same after a full turn, and its mirror image is different
+        let l = "A.\nBC".parse::<Stencil>().unwrap();
+        assert_eq!(l.variants(false).len(), 4);
+        assert_eq!(l.variants(true).len(), 8);
+    }
+
+    #[test]
+    fn stencils_in_grid() {
+        let search = WordSearch::new("M.S.M\n.A.A.\nM.S.M");
+        let cross = "M.S\n.A.\nM.S".parse::<Stencil>().unwrap();
+        assert_eq!(
+            search.find_stencils(std::slice::from_ref(&cross)),
+            [IVec2::new(0, 0)]
+        );
+        assert_eq!(
+            search.find_stencils(&cross.variants(true)),
+            [IVec2::new(0, 0), IVec2::new(2, 0)]
+        );
+    }
+}